@@ -1,16 +1,54 @@
-use hexx::common::board::{CellState, HexBoard};
-use hexx::common::player::Player;
-use hexx::simulator::save_bridge_simulator::SaveBridgeSimulator;
-use hexx::simulator::simulator::Simulator;
+use hexx::common::moves::Move;
+use hexx::game::game::Game;
 use hexx::solver::mcts_solver::MCTSSolver;
 use hexx::solver::solver::Solver;
+use hexx::visualize::visualize::{heatmap_html, tree_to_dot};
 
+fn write_file(file: &str, content: &str) {
+    if let Err(e) = std::fs::write(file, content) {
+        eprintln!("cannot write {}: {}", file, e);
+        std::process::exit(1);
+    }
+}
+
+/// usage: game [--dot FILE] [--html FILE] [--depth N]
+/// writes the search tree as Graphviz DOT and the root moves as an HTML heatmap
 fn main() {
-    let mut board = HexBoard::new(11);
-    board.set(5, 5, CellState::Red);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut dot_file = None;
+    let mut html_file = None;
+    let mut depth = 2;
+    for pair in args.chunks(2) {
+        match (pair[0].as_str(), pair.get(1)) {
+            ("--dot", Some(file)) => dot_file = Some(file.clone()),
+            ("--html", Some(file)) => html_file = Some(file.clone()),
+            ("--depth", Some(value)) => depth = match value.parse() {
+                Ok(x) => x,
+                Err(_) => {
+                    eprintln!("invalid depth: {}", value);
+                    std::process::exit(1);
+                }
+            },
+            _ => {
+                eprintln!("usage: game [--dot FILE] [--html FILE] [--depth N]");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let mut game = Game::new(11);
+    game.play(Move::place(5, 5)).unwrap();
+
+    let mut solver = MCTSSolver::new(Default::default());
+    let cell = solver.solve(&game.board, game.next_player()).best_move;
 
-    let solver = MCTSSolver::new(Default::default());
-    let (x, y) = solver.solve(&board, Player::Blue);
+    println!("{}", cell);
 
-    println!("{}, {}", x, y);
+    let tree = solver.tree().unwrap();
+    if let Some(file) = dot_file {
+        write_file(&file, &tree_to_dot(tree, depth));
+    }
+    if let Some(file) = html_file {
+        write_file(&file, &heatmap_html(tree));
+    }
 }
@@ -0,0 +1,11 @@
+use hexx::protocol::htp::HtpEngine;
+use hexx::solver::mcts_solver::MCTSSolver;
+
+fn main() {
+    let solver = MCTSSolver::new(Default::default());
+    let mut engine = HtpEngine::new(11, Box::new(solver));
+
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    engine.run(stdin.lock(), stdout.lock()).unwrap();
+}
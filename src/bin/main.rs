@@ -1,39 +1,51 @@
-use hexx::common::board::{CellState, HexBoard};
-use hexx::common::player::Player;
-use hexx::simulator::save_bridge_simulator::SaveBridgeSimulator;
-use hexx::simulator::simulator::Simulator;
+use hexx::common::moves::Move;
+use hexx::game::game::Game;
 use hexx::solver::mcts_solver::MCTSSolver;
 use hexx::solver::solver::Solver;
 
 fn main() {
-    let mut board = HexBoard::new(11);
+    let mut game = Game::new(11);
+    let mut solver = MCTSSolver::new(Default::default());
 
     loop {
         let mut line = String::new();
-        std::io::stdin().read_line(&mut line).unwrap();
-
-        let coords: Vec<usize> = line.split(' ').map(|x| x.trim().parse().unwrap()).collect();
-        let (x, y) = (coords[0], coords[1]);
-
-        board.set(x, y, CellState::Red);
-        println!("{}", board);
-
-        let solver = MCTSSolver::new(Default::default());
-        let (x, y) = solver.solve(&board, Player::Blue);
-
-        board.set(x, y, CellState::Blue);
-        println!("{}", board);
+        if std::io::stdin().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+
+        // take back the last move of the engine and our move before it
+        if line.trim() == "undo" {
+            game.undo();
+            game.undo();
+            println!("{}", game.board);
+            continue;
+        }
+
+        let mov = match Move::parse(line.trim(), game.board.width, game.board.height) {
+            Ok(x) => x,
+            Err(e) => {
+                println!("{}, expected a cell like \"f6\", \"resign\" or \"undo\"", e);
+                continue;
+            }
+        };
+        if let Err(e) = game.play(mov) {
+            println!("{}", e);
+            continue;
+        }
+        println!("{}", game.board);
+        if game.is_over() {
+            break;
+        }
+
+        let mov = Move::Place(solver.solve(&game.board, game.next_player()).best_move);
+        game.play(mov).unwrap();
+        println!("{}\n{}", mov, game.board);
+        if game.is_over() {
+            break;
+        }
     }
-    board.set(5, 5, CellState::Red);
-    // let simulator = SaveBridgeSimulator;
-    //
-    // let count = simulator.simulate(&board, Player::Blue, 10000);
-    //
-    // println!("red wins: {}", count);
-
-    // let solver = MCTSSolver::new(Default::default());
-    // let (x, y) = solver.solve(&board, Player::Blue);
 
-    println!("{}", board);
-    // println!("{}, {}", x, y);
+    if let Some(winner) = game.winner() {
+        println!("{:?} wins", winner);
+    }
 }
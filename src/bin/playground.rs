@@ -26,8 +26,8 @@ fn main() {
 
     println!("{}", board);
 
-    let solver = MCTSSolver::new(Default::default());
-    let (x, y) = solver.solve(&board, Player::Blue);
+    let mut solver = MCTSSolver::new(Default::default());
+    let cell = solver.solve(&board, Player::Blue).best_move;
 
-    println!("{}, {}", x, y);
+    println!("{}", cell);
 }
\ No newline at end of file
@@ -0,0 +1,310 @@
+use lazy_static::lazy_static;
+use crate::common::board::{CellState, HexBoard};
+use crate::common::cell_set::CellSet;
+use crate::common::constants::MAX_SIZE;
+use crate::common::player::Player;
+
+/// masks of the rows and columns of one board shape, used to keep shifts from wrapping around a row
+pub struct ShiftMasks {
+    pub board: CellSet,
+    pub first_row: CellSet,
+    pub last_row: CellSet,
+    pub first_col: CellSet,
+    pub last_col: CellSet,
+}
+
+impl ShiftMasks {
+    fn new(width: usize, height: usize) -> ShiftMasks {
+        let mut result = ShiftMasks {
+            board: CellSet::full(width * height),
+            first_row: CellSet::new(),
+            last_row: CellSet::new(),
+            first_col: CellSet::new(),
+            last_col: CellSet::new(),
+        };
+        if width == 0 || height == 0 {
+            return result;
+        }
+        for y in 0..width {
+            result.first_row.insert(y);
+            result.last_row.insert((height - 1) * width + y);
+        }
+        for x in 0..height {
+            result.first_col.insert(x * width);
+            result.last_col.insert(x * width + width - 1);
+        }
+        result
+    }
+}
+
+lazy_static! {
+    /// shift masks of every board shape, indexed by the height and the width
+    pub static ref SHIFT_MASKS: Vec<Vec<ShiftMasks>> = (0..=MAX_SIZE)
+        .map(|height| (0..=MAX_SIZE).map(|width| ShiftMasks::new(width, height)).collect())
+        .collect();
+}
+
+/// A board stored as one bit mask per color, cells are indexed by `x * width + y` like `HexBoard::get_abs`.
+///
+/// Neighbors are computed for whole sets of cells at once by shifting the masks,
+/// so flood fills take one step per distance instead of one step per cell
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BitBoard {
+    pub width: usize,
+    pub height: usize,
+    pub red: CellSet,
+    pub blue: CellSet,
+}
+
+impl BitBoard {
+    pub fn new(width: usize, height: usize) -> BitBoard {
+        BitBoard {
+            width,
+            height,
+            red: CellSet::new(),
+            blue: CellSet::new(),
+        }
+    }
+
+    pub fn masks(&self) -> &'static ShiftMasks {
+        &SHIFT_MASKS[self.height][self.width]
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> CellState {
+        self.get_abs(x * self.width + y)
+    }
+
+    pub fn get_abs(&self, pos: usize) -> CellState {
+        if self.red.contains(pos) {
+            CellState::Red
+        } else if self.blue.contains(pos) {
+            CellState::Blue
+        } else {
+            CellState::Empty
+        }
+    }
+
+    /// like `HexBoard::get_with_padding`, rows outside the board are red and columns outside the board are blue
+    pub fn get_with_padding(&self, x: i32, y: i32) -> CellState {
+        if x < 0 || x >= self.height as i32 {
+            CellState::Red
+        } else if y < 0 || y >= self.width as i32 {
+            CellState::Blue
+        } else {
+            self.get(x as usize, y as usize)
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: CellState) {
+        let pos = x * self.width + y;
+        self.red.remove(pos);
+        self.blue.remove(pos);
+        match value {
+            CellState::Red => self.red.insert(pos),
+            CellState::Blue => self.blue.insert(pos),
+            CellState::Empty => {}
+        }
+    }
+
+    pub fn stones(&self, player: Player) -> CellSet {
+        if player == Player::Red { self.red } else { self.blue }
+    }
+
+    pub fn empty(&self) -> CellSet {
+        self.masks().board - self.red - self.blue
+    }
+
+    /// cells adjacent to any cell of `cells`, the cells themselves are not included unless adjacent to another one
+    pub fn neighbors(&self, cells: CellSet) -> CellSet {
+        let width = self.width;
+        let masks = self.masks();
+        let not_first_col = cells - masks.first_col;
+        let not_last_col = cells - masks.last_col;
+
+        let result = (not_first_col >> 1)
+            | (not_last_col << 1)
+            | (cells >> width)
+            | (cells << width)
+            | (not_last_col >> (width - 1))
+            | (not_first_col << (width - 1));
+        result & masks.board
+    }
+
+    /// the cells whose row `x + dx` is on the board
+    fn rows_within(&self, dx: i32) -> CellSet {
+        let masks = self.masks();
+        let mut result = masks.board;
+        for k in 0..dx.unsigned_abs() as usize {
+            result = result - if dx > 0 { masks.last_row >> (k * self.width) } else { masks.first_row << (k * self.width) };
+        }
+        result
+    }
+
+    /// the cells whose column `y + dy` is on the board
+    fn cols_within(&self, dy: i32) -> CellSet {
+        let masks = self.masks();
+        let mut result = masks.board;
+        for k in 0..dy.unsigned_abs() as usize {
+            result = result - if dy > 0 { masks.last_col >> k } else { masks.first_col << k };
+        }
+        result
+    }
+
+    /// the cells (x, y) such that (x + dx, y + dy) is on the board and in `cells`
+    pub fn shift(&self, cells: CellSet, dx: i32, dy: i32) -> CellSet {
+        let offset = dx * self.width as i32 + dy;
+        let moved = if offset >= 0 { cells >> offset as usize } else { cells << offset.unsigned_abs() as usize };
+        moved & self.rows_within(dx) & self.cols_within(dy)
+    }
+
+    /// the cells (x, y) such that `get_with_padding(x + dx, y + dy)` is red, and those where it is blue
+    pub fn shifted_stones(&self, dx: i32, dy: i32) -> (CellSet, CellSet) {
+        let rows = self.rows_within(dx);
+        let red = self.shift(self.red, dx, dy) | (self.masks().board - rows);
+        let blue = self.shift(self.blue, dx, dy) | (rows - self.cols_within(dy));
+        (red, blue)
+    }
+
+    /// the cells of `within` connected to `seed` through `within`
+    pub fn flood(&self, seed: CellSet, within: CellSet) -> CellSet {
+        let mut current = seed & within;
+        loop {
+            let next = (current | self.neighbors(current)) & within;
+            if next == current {
+                return current;
+            }
+            current = next;
+        }
+    }
+
+    pub fn winner_red(&self) -> bool {
+        let masks = self.masks();
+        let reached = self.flood(masks.first_row, self.red);
+        !reached.is_disjoint(&masks.last_row)
+    }
+
+    pub fn winner_blue(&self) -> bool {
+        let masks = self.masks();
+        let reached = self.flood(masks.first_col, self.blue);
+        !reached.is_disjoint(&masks.last_col)
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        if self.winner_red() {
+            Some(Player::Red)
+        } else if self.winner_blue() {
+            Some(Player::Blue)
+        } else {
+            None
+        }
+    }
+
+    pub fn to_hex_board(&self) -> HexBoard {
+        let mut result = HexBoard::new_rect(self.width, self.height);
+        for pos in self.red.iter() {
+            result.set_abs(pos, CellState::Red);
+        }
+        for pos in self.blue.iter() {
+            result.set_abs(pos, CellState::Blue);
+        }
+        result
+    }
+}
+
+impl From<&HexBoard> for BitBoard {
+    fn from(board: &HexBoard) -> Self {
+        let (red, blue) = board.cell_sets();
+        BitBoard {
+            width: board.width,
+            height: board.height,
+            red,
+            blue,
+        }
+    }
+}
+
+impl From<&BitBoard> for HexBoard {
+    fn from(board: &BitBoard) -> Self {
+        board.to_hex_board()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::common::bit_board::BitBoard;
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::cell_set::CellSet;
+
+    fn random_board(rng: &mut StdRng, width: usize, height: usize, stones: usize) -> HexBoard {
+        let mut board = HexBoard::new_rect(width, height);
+        for _ in 0..stones {
+            let pos = rng.gen_range(0..width * height);
+            let cell = if rng.gen_bool(0.5) { CellState::Red } else { CellState::Blue };
+            board.set_abs(pos, cell);
+        }
+        board
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let board = BitBoard::new(5, 5);
+        let center: CellSet = [12].into_iter().collect();
+        let expected: CellSet = [11, 7, 8, 13, 17, 16].into_iter().collect();
+        assert_eq!(board.neighbors(center), expected);
+
+        // no wrapping around the rows
+        let corner: CellSet = [4].into_iter().collect();
+        let expected: CellSet = [3, 8, 9].into_iter().collect();
+        assert_eq!(board.neighbors(corner), expected);
+        let corner: CellSet = [20].into_iter().collect();
+        let expected: CellSet = [15, 16, 21].into_iter().collect();
+        assert_eq!(board.neighbors(corner), expected);
+
+        assert!(BitBoard::new(1, 1).neighbors(CellSet::full(1)).is_empty());
+
+        // 3 columns and 2 rows
+        let board = BitBoard::new(3, 2);
+        let corner: CellSet = [2].into_iter().collect();
+        let expected: CellSet = [1, 4, 5].into_iter().collect();
+        assert_eq!(board.neighbors(corner), expected);
+    }
+
+    #[test]
+    fn test_conversion_and_winner() {
+        let mut rng = StdRng::seed_from_u64(18);
+        for (width, height) in [(1, 1), (2, 2), (5, 5), (11, 11), (19, 19), (25, 25), (5, 6), (8, 7), (1, 4)] {
+            let count = width * height;
+            for stones in [0, width, count / 2, count] {
+                let board = random_board(&mut rng, width, height, stones);
+                let bit_board = BitBoard::from(&board);
+
+                assert!(bit_board.to_hex_board() == board);
+                assert_eq!(bit_board.winner_red(), board.winner_red());
+                assert_eq!(bit_board.winner_blue(), board.winner_blue());
+                for pos in 0..count {
+                    assert_eq!(bit_board.get_abs(pos), board.get_abs(pos));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for (width, height) in [(7, 6), (1, 1), (2, 1), (1, 3), (2, 2), (11, 11), (25, 25), (25, 3)] {
+            let board = random_board(&mut rng, width, height, width * height / 2);
+            let bit_board = BitBoard::from(&board);
+            let encodings1 = bit_board.encodings1();
+            let encodings2 = ['v', 'h', 'd'].map(|d| bit_board.encodings2(d));
+            for pos in 0..width * height {
+                let (x, y) = (pos / width, pos % width);
+                assert_eq!(encodings1[pos], board.get_encoding1(x, y));
+                for (i, d) in ['v', 'h', 'd'].into_iter().enumerate() {
+                    assert_eq!(encodings2[i][pos], board.get_encoding2(x, y, d));
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,16 @@
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 use ansi_term::Colour::{Blue, Red};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use crate::common::constants::{DIS1, MAX_SIZE};
+use crate::common::moves::Move;
 use crate::common::player::Player;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum CellState {
     Empty,
     Red,
@@ -41,38 +48,337 @@ impl CellState {
     }
 }
 
-#[derive(Clone)]
+/// Zobrist keys of the whole board, generated from a fixed seed so that hashes are stable between runs
+pub struct BoardZobristKeys {
+    cell_keys: [[[u64; 2]; MAX_SIZE]; MAX_SIZE],
+    /// keys of the shapes, indexed by height and width
+    size_keys: [[u64; MAX_SIZE + 1]; MAX_SIZE + 1],
+    blue_to_move_key: u64,
+}
+
+impl BoardZobristKeys {
+    fn new() -> Self {
+        let mut rng = StdRng::seed_from_u64(0x6865_7878);
+        let mut result = BoardZobristKeys {
+            cell_keys: [[[0; 2]; MAX_SIZE]; MAX_SIZE],
+            size_keys: [[0; MAX_SIZE + 1]; MAX_SIZE + 1],
+            blue_to_move_key: 0,
+        };
+
+        for row in result.cell_keys.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = [rng.gen(), rng.gen()];
+            }
+        }
+        for row in result.size_keys.iter_mut() {
+            for key in row.iter_mut() {
+                *key = rng.gen();
+            }
+        }
+        result.blue_to_move_key = rng.gen();
+
+        result
+    }
+
+    fn cell_key(&self, x: usize, y: usize, cell: CellState) -> u64 {
+        match cell {
+            CellState::Empty => 0,
+            CellState::Red => self.cell_keys[x][y][0],
+            CellState::Blue => self.cell_keys[x][y][1],
+        }
+    }
+}
+
+lazy_static! {
+    pub static ref BOARD_ZOBRIST_KEYS: BoardZobristKeys = BoardZobristKeys::new();
+}
+
+/// union-find nodes of the edges, counted after the nodes of the cells
+const RED_TOP: usize = 0;
+const RED_BOTTOM: usize = 1;
+const BLUE_LEFT: usize = 2;
+const BLUE_RIGHT: usize = 3;
+const EDGE_NODES: usize = 4;
+
+/// The board of a game.
+///
+/// Red connects the first and the last row, blue the first and the last column.
+/// A cell is given by its row `x` and its column `y`, or by its position `x * width + y`.
+///
+/// The cells and the groups are stored for the `width * height` cells of the board only.
+/// Besides the cells, the board keeps a union-find of the groups of stones and the four edges,
+/// updated by `set`, so that `winner` does not search the board.
+/// Removing the last placed stone undoes its unions, so make and unmake in stack order stay cheap.
+/// Removing any other stone or recoloring one rebuilds the groups in O(cells),
+/// and code writing `data` directly must call `rebuild` afterwards
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "BoardData", try_from = "BoardData")]
 pub struct HexBoard {
-    pub size: usize,
-    pub data: [[CellState; MAX_SIZE]; MAX_SIZE],
+    /// number of columns
+    pub width: usize,
+    /// number of rows
+    pub height: usize,
+    /// the cells by position
+    pub data: Vec<CellState>,
     pub empty_count: usize,
+    /// Zobrist hash of the shape and the stones, updated by `set`
+    pub hash: u64,
+    parent: Vec<u16>,
+    rank: Vec<u8>,
+    /// unions since the last rebuild: the root that got a parent, and whether the rank of the new root grew
+    unions: Vec<(u16, bool)>,
+    /// stones placed since the last rebuild, with the length of `unions` before each
+    placed: Vec<(u16, usize)>,
+}
+
+/// the serialized form of `HexBoard`, the rows are written like `to_text`
+#[derive(Serialize, Deserialize)]
+struct BoardData {
+    width: usize,
+    height: usize,
+    rows: Vec<String>,
+}
+
+impl From<HexBoard> for BoardData {
+    fn from(board: HexBoard) -> Self {
+        BoardData {
+            width: board.width,
+            height: board.height,
+            rows: board.to_text().lines().map(String::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<BoardData> for HexBoard {
+    type Error = String;
+
+    fn try_from(data: BoardData) -> Result<Self, Self::Error> {
+        let board: HexBoard = data.rows.join("\n").parse()?;
+        if board.width != data.width || board.height != data.height {
+            return Err(format!("rows do not match the size {}x{}", data.width, data.height));
+        }
+        Ok(board)
+    }
+}
+
+impl PartialEq for HexBoard {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.hash == other.hash && self.data == other.data
+    }
+}
+
+impl Eq for HexBoard {}
+
+impl Hash for HexBoard {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
 }
 
 impl HexBoard {
+    /// a square board
     pub fn new(size: usize) -> HexBoard {
+        HexBoard::new_rect(size, size)
+    }
+
+    /// a board of `height` rows with `width` cells each, both at most `MAX_SIZE`
+    pub fn new_rect(width: usize, height: usize) -> HexBoard {
+        assert!(width > 0 && height > 0 && width <= MAX_SIZE && height <= MAX_SIZE, "board size out of range");
         HexBoard {
-            size,
-            data: [[CellState::Empty; MAX_SIZE]; MAX_SIZE],
-            empty_count: size * size,
+            width,
+            height,
+            data: vec![CellState::Empty; width * height],
+            empty_count: width * height,
+            hash: BOARD_ZOBRIST_KEYS.size_keys[height][width],
+            parent: (0..width * height + EDGE_NODES).map(|i| i as u16).collect(),
+            rank: vec![0; width * height + EDGE_NODES],
+            unions: Vec::new(),
+            placed: Vec::new(),
+        }
+    }
+
+    /// number of cells, positions are below it
+    pub fn cell_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// whether the board has as many rows as columns
+    pub fn is_square(&self) -> bool {
+        self.width == self.height
+    }
+
+    fn find(&self, mut node: usize) -> usize {
+        while self.parent[node] as usize != node {
+            node = self.parent[node] as usize;
+        }
+        node
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        if self.rank[a] < self.rank[b] {
+            self.parent[a] = b as u16;
+            self.unions.push((a as u16, false));
+        } else {
+            self.parent[b] = a as u16;
+            let grown = self.rank[a] == self.rank[b];
+            if grown {
+                self.rank[a] += 1;
+            }
+            self.unions.push((b as u16, grown));
+        }
+    }
+
+    /// take back the unions of the last placed stone, `find` does not compress paths so they are undone exactly
+    fn unjoin_last_stone(&mut self) {
+        let (_, len) = self.placed.pop().unwrap();
+        while self.unions.len() > len {
+            let (node, grown) = self.unions.pop().unwrap();
+            let root = self.parent[node as usize] as usize;
+            self.parent[node as usize] = node;
+            if grown {
+                self.rank[root] -= 1;
+            }
+        }
+    }
+
+    /// join a new stone with its neighbors of the same color and the edges it touches
+    fn join_stone(&mut self, x: usize, y: usize) {
+        let (width, height) = (self.width, self.height);
+        let pos = x * width + y;
+        let cell = self.data[pos];
+        self.placed.push((pos as u16, self.unions.len()));
+
+        for d in DIS1.iter() {
+            let (nx, ny) = (x as i32 + d[0], y as i32 + d[1]);
+            if nx >= 0 && nx < height as i32 && ny >= 0 && ny < width as i32 && self.data[nx as usize * width + ny as usize] == cell {
+                self.union(pos, nx as usize * width + ny as usize);
+            }
+        }
+
+        let edges = width * height;
+        if cell == CellState::Red {
+            if x == 0 {
+                self.union(pos, edges + RED_TOP);
+            }
+            if x == height - 1 {
+                self.union(pos, edges + RED_BOTTOM);
+            }
+        } else {
+            if y == 0 {
+                self.union(pos, edges + BLUE_LEFT);
+            }
+            if y == width - 1 {
+                self.union(pos, edges + BLUE_RIGHT);
+            }
+        }
+    }
+
+    /// recompute the groups and the hash from `data`
+    pub fn rebuild(&mut self) {
+        let nodes = self.cell_count() + EDGE_NODES;
+        self.parent = (0..nodes).map(|i| i as u16).collect();
+        self.rank = vec![0; nodes];
+        for x in 0..self.height {
+            for y in 0..self.width {
+                if self.get(x, y) != CellState::Empty {
+                    self.join_stone(x, y);
+                }
+            }
+        }
+        self.unions.clear();
+        self.placed.clear();
+        self.hash = self.compute_hash();
+    }
+
+    /// whether the two stones are in the same group, false when a cell is empty
+    pub fn is_connected(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let (ca, cb) = (self.get(a.0, a.1), self.get(b.0, b.1));
+        ca != CellState::Empty && ca == cb
+            && self.find(a.0 * self.width + a.1) == self.find(b.0 * self.width + b.1)
+    }
+
+    /// hash of the position with `next_player` to move
+    pub fn hash_with_player(&self, next_player: Player) -> u64 {
+        if next_player == Player::Blue {
+            self.hash ^ BOARD_ZOBRIST_KEYS.blue_to_move_key
+        } else {
+            self.hash
+        }
+    }
+
+    /// `hash_with_player` of the position after `player` places a stone at the empty cell `pos`
+    pub fn hash_after_move(&self, pos: usize, player: Player) -> u64 {
+        let (x, y) = (pos / self.width, pos % self.width);
+        let hash = self.hash ^ BOARD_ZOBRIST_KEYS.cell_key(x, y, player.to_cell());
+        if player == Player::Red {
+            hash ^ BOARD_ZOBRIST_KEYS.blue_to_move_key
+        } else {
+            hash
         }
     }
 
     pub fn rotate180(&self) -> Self {
-        let mut result = self.clone();
+        let mut result = HexBoard::new_rect(self.width, self.height);
 
-        for i in 0..self.size {
-            for j in 0..self.size - i {
-                let temp = result.data[i][j];
-                result.data[i][j] = result.data[self.size - i - 1][self.size - j - 1];
-                result.data[self.size - i - 1][self.size - j - 1] = temp;
+        for x in 0..self.height {
+            for y in 0..self.width {
+                result.set(self.height - x - 1, self.width - y - 1, self.get(x, y));
             }
         }
 
         result
     }
 
+    /// hash of the board computed from scratch, for code that writes `data` directly
+    pub fn compute_hash(&self) -> u64 {
+        let mut hash = BOARD_ZOBRIST_KEYS.size_keys[self.height][self.width];
+        for x in 0..self.height {
+            for y in 0..self.width {
+                hash ^= BOARD_ZOBRIST_KEYS.cell_key(x, y, self.get(x, y));
+            }
+        }
+        hash
+    }
+
+    /// the board after the swap move: every stone is mirrored along the long diagonal and changes color,
+    /// the rows become columns so a rectangular board changes its shape
+    pub fn swap_pieces(&self) -> Self {
+        let mut result = HexBoard::new_rect(self.height, self.width);
+
+        for i in 0..self.height {
+            for j in 0..self.width {
+                result.set(j, i, self.get(i, j).reverse());
+            }
+        }
+
+        result
+    }
+
+    /// whether `next_player` may swap, i.e. the board only holds the first stone of red
+    pub fn can_swap(&self, next_player: Player) -> bool {
+        if next_player != Player::Blue || self.empty_count + 1 != self.cell_count() {
+            return false;
+        }
+
+        (0..self.cell_count()).any(|i| self.get_abs(i) == CellState::Red)
+    }
+
+    pub fn play(&mut self, mov: Move, player: Player) {
+        match mov {
+            Move::Place(cell) => self.set(cell.x, cell.y, player.to_cell()),
+            Move::Swap => *self = self.swap_pieces(),
+            // the position does not change, the game is over
+            Move::Resign => {}
+        }
+    }
+
     pub fn fill_row(&mut self, row: usize, s: &str) {
-        assert_eq!(s.len(), self.size);
+        assert_eq!(s.len(), self.width);
         for (index, c) in s.chars().enumerate() {
             if c == 'r' {
                 self.set(row, index, CellState::Red);
@@ -89,32 +395,46 @@ impl HexBoard {
     }
 
     pub fn set_abs(&mut self, pos: usize, value: CellState) {
-        self.set(pos / self.size, pos % self.size, value)
+        self.set(pos / self.width, pos % self.width, value)
     }
 
     pub fn set(&mut self, x: usize, y: usize, value: CellState) {
-        let old_value = self.data[x][y];
+        let old_value = self.get(x, y);
         if old_value == CellState::Empty && value != CellState::Empty {
             self.empty_count -= 1;
         } else if old_value != CellState::Empty && value == CellState::Empty {
             self.empty_count += 1;
         }
 
-        self.data[x][y] = value;
+        self.hash ^= BOARD_ZOBRIST_KEYS.cell_key(x, y, old_value) ^ BOARD_ZOBRIST_KEYS.cell_key(x, y, value);
+        self.data[x * self.width + y] = value;
+
+        if old_value == value {
+            return;
+        }
+        let pos = x * self.width + y;
+        if value == CellState::Empty && self.placed.last().map(|&(p, _)| p as usize) == Some(pos) {
+            self.unjoin_last_stone();
+        } else if old_value != CellState::Empty {
+            // union-find cannot split groups
+            self.rebuild();
+        } else {
+            self.join_stone(x, y);
+        }
     }
 
     pub fn get(&self, x: usize, y: usize) -> CellState {
-        self.data[x][y]
+        self.data[x * self.width + y]
     }
 
     pub fn get_abs(&self, pos: usize) -> CellState {
-        self.get(pos / self.size, pos % self.size)
+        self.data[pos]
     }
 
     pub fn get_with_padding(&self, x: i32, y: i32) -> CellState {
-        if x < 0 || x >= self.size as i32 {
+        if x < 0 || x >= self.height as i32 {
             CellState::Red
-        } else if y < 0 || y >= self.size as i32 {
+        } else if y < 0 || y >= self.width as i32 {
             CellState::Blue
         } else {
             self.get(x as usize, y as usize)
@@ -122,79 +442,17 @@ impl HexBoard {
     }
 
     pub fn get_abs_with_padding(&self, pos: i32) -> CellState {
-        self.get_with_padding(pos / self.size as i32, pos % self.size as i32)
+        self.get_with_padding(pos / self.width as i32, pos % self.width as i32)
     }
 
     pub fn winner_blue(&self) -> bool {
-        let size = self.size as i32;
-
-        let mut stack: Vec<(i32, i32)> = Vec::new();
-        let mut vis = [[false; MAX_SIZE]; MAX_SIZE];
-
-        for i in 0..self.size {
-            if self.get(i, 0) == CellState::Blue {
-                stack.push((i as i32, 0));
-                vis[i][0] = true;
-            }
-        }
-
-        while !stack.is_empty() {
-            let (x, y) = stack.pop().unwrap();
-
-            for i in 0..6_usize {
-                let (nx, ny) = (x + DIS1[i][0], y + DIS1[i][1]);
-
-                if nx >= 0 && nx < size && ny >= 0 && ny < size && !vis[nx as usize][ny as usize] {
-                    vis[nx as usize][ny as usize] = true;
-                    let cell = self.get(nx as usize, ny as usize);
-                    if cell == CellState::Blue {
-                        if ny == size - 1 {
-                            return true;
-                        }
-
-                        stack.push((nx, ny))
-                    }
-                }
-            }
-        }
-
-        false
+        let edges = self.cell_count();
+        self.find(edges + BLUE_LEFT) == self.find(edges + BLUE_RIGHT)
     }
 
     pub fn winner_red(&self) -> bool {
-        let size = self.size as i32;
-
-        let mut stack: Vec<(i32, i32)> = Vec::new();
-        let mut vis = [[false; MAX_SIZE]; MAX_SIZE];
-
-        for i in 0..self.size {
-            if self.get(0, i) == CellState::Red {
-                stack.push((0, i as i32));
-                vis[0][i] = true;
-            }
-        }
-
-        while !stack.is_empty() {
-            let (x, y) = stack.pop().unwrap();
-
-            for i in 0..6_usize {
-                let (nx, ny) = (x + DIS1[i][0], y + DIS1[i][1]);
-
-                if nx >= 0 && nx < size && ny >= 0 && ny < size && !vis[nx as usize][ny as usize] {
-                    vis[nx as usize][ny as usize] = true;
-                    let cell = self.get(nx as usize, ny as usize);
-                    if cell == CellState::Red {
-                        if nx == size - 1 {
-                            return true;
-                        }
-
-                        stack.push((nx, ny))
-                    }
-                }
-            }
-        }
-
-        false
+        let edges = self.cell_count();
+        self.find(edges + RED_TOP) == self.find(edges + RED_BOTTOM)
     }
 
     /// get winner, make sure there is a winner, no draws
@@ -217,14 +475,13 @@ impl HexBoard {
     }
 
     pub fn get_position(&self, x: i32, y: i32) -> BoardPosition {
-        let size = self.size as i32;
         if x < 0 {
             BoardPosition::LeftTop
-        } else if x >= size {
+        } else if x >= self.height as i32 {
             BoardPosition::RightBottom
         } else if y < 0 {
             BoardPosition::LeftBottom
-        } else if y >= size {
+        } else if y >= self.width as i32 {
             BoardPosition::RightTop
         } else {
             let cell = self.get(x as usize, y as usize);
@@ -233,14 +490,147 @@ impl HexBoard {
     }
 
     pub fn get_position_abs(&self, pos: i32) -> BoardPosition {
-        let size = self.size as i32;
-        self.get_position(pos / size, pos % size)
+        let width = self.width as i32;
+        self.get_position(pos / width, pos % width)
+    }
+}
+
+impl HexBoard {
+    /// the rows of the board on separate lines, `r` for red, `b` for blue and `_` for empty cells,
+    /// like the arguments of `fill_row`
+    pub fn to_text(&self) -> String {
+        (0..self.height)
+            .map(|x| (0..self.width).map(|y| cell_char(self.get(x, y))).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// the rows on one line separated by `/`, runs of empty cells are written as their length,
+    /// e.g. `1r2/1r2/r1b1/r3`
+    pub fn to_compact(&self) -> String {
+        let mut rows = Vec::new();
+        for x in 0..self.height {
+            let mut row = String::new();
+            let mut empty = 0;
+            for y in 0..self.width {
+                let cell = self.get(x, y);
+                if cell == CellState::Empty {
+                    empty += 1;
+                    continue;
+                }
+                if empty > 0 {
+                    row += &empty.to_string();
+                    empty = 0;
+                }
+                row.push(cell_char(cell));
+            }
+            if empty > 0 {
+                row += &empty.to_string();
+            }
+            rows.push(row);
+        }
+        rows.join("/")
+    }
+
+    /// the board as a rhombus without colors, with the column letters above and below
+    /// and the row numbers on both sides
+    pub fn to_labeled_string(&self) -> String {
+        let letters = (0..self.width).map(|y| ((b'a' + y as u8) as char).to_string()).collect::<Vec<_>>().join(" ");
+        let mut lines = vec![format!("   {}", letters)];
+        for x in 0..self.height {
+            let cells = (0..self.width).map(|y| cell_char(self.get(x, y)).to_string()).collect::<Vec<_>>().join(" ");
+            lines.push(format!("{}{:>2} {} {}", " ".repeat(x), x + 1, cells, x + 1));
+        }
+        lines.push(format!("{}   {}", " ".repeat(self.height), letters));
+        lines.join("\n")
+    }
+}
+
+fn cell_char(cell: CellState) -> char {
+    match cell {
+        CellState::Red => 'r',
+        CellState::Blue => 'b',
+        CellState::Empty => '_',
+    }
+}
+
+fn parse_cell_char(c: char) -> Result<CellState, String> {
+    match c {
+        'r' => Ok(CellState::Red),
+        'b' => Ok(CellState::Blue),
+        '_' | '.' => Ok(CellState::Empty),
+        _ => Err(format!("invalid cell: '{}'", c))
+    }
+}
+
+/// parse a board written by `to_text`, `to_compact` or `to_labeled_string`.
+///
+/// A board of only digits, cells and `/` is in the compact form, its rows are separated
+/// by `/` and a number stands for that many empty cells. Otherwise every line is a row, spaces are ignored
+/// and so are the labels: numbers and lines of column letters `a b c ...`.
+/// Empty cells may also be written as `.`
+impl FromStr for HexBoard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rows: Vec<Vec<CellState>> = Vec::new();
+
+        let is_compact = !s.trim().is_empty()
+            && s.trim().chars().all(|c| c.is_ascii_digit() || "/rb_.".contains(c));
+        if is_compact {
+            for row in s.trim().split('/') {
+                let mut cells = Vec::new();
+                let mut empty = 0usize;
+                for c in row.chars() {
+                    if let Some(digit) = c.to_digit(10) {
+                        empty = empty * 10 + digit as usize;
+                        if cells.len() + empty > MAX_SIZE {
+                            return Err(format!("board size must be between 1 and {}", MAX_SIZE));
+                        }
+                        continue;
+                    }
+                    cells.extend(std::iter::repeat_n(CellState::Empty, empty));
+                    empty = 0;
+                    cells.push(parse_cell_char(c)?);
+                }
+                cells.extend(std::iter::repeat_n(CellState::Empty, empty));
+                rows.push(cells);
+            }
+        } else {
+            for line in s.lines() {
+                let tokens: Vec<&str> = line.split_whitespace()
+                    .filter(|x| x.parse::<usize>().is_err())
+                    .collect();
+                let is_header = !tokens.is_empty() && tokens.iter().enumerate()
+                    .all(|(i, x)| x.len() == 1 && x.as_bytes()[0] == b'a' + i as u8);
+                if tokens.is_empty() || is_header {
+                    continue;
+                }
+                rows.push(tokens.concat().chars().map(parse_cell_char).collect::<Result<_, _>>()?);
+            }
+        }
+
+        let (width, height) = (rows.first().map(|x| x.len()).unwrap_or(0), rows.len());
+        if width == 0 || width > MAX_SIZE || height > MAX_SIZE {
+            return Err(format!("board size must be between 1 and {}", MAX_SIZE));
+        }
+        if rows.iter().any(|x| x.len() != width) {
+            return Err(String::from("rows have different lengths"));
+        }
+
+        let mut board = HexBoard::new_rect(width, height);
+        for (x, row) in rows.iter().enumerate() {
+            for (y, &cell) in row.iter().enumerate() {
+                board.set(x, y, cell);
+            }
+        }
+        Ok(board)
     }
 }
 
 impl Display for HexBoard {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut write_cell = |f: &mut Formatter<'_>, cell: CellState| -> std::fmt::Result {
+        let write_cell = |f: &mut Formatter<'_>, cell: CellState| -> std::fmt::Result {
             match cell {
                 CellState::Red => write!(f, "{}", Red.paint("x")),
                 CellState::Blue => write!(f, "{}", Blue.paint("x")),
@@ -248,16 +638,16 @@ impl Display for HexBoard {
             }
         };
 
-        for x in 0..self.size {
+        for x in 0..self.height {
             write!(f, "{}", " ".repeat(x))?;
 
-            for y in 0..self.size {
+            for y in 0..self.width {
                 write_cell(f, self.get(x, y))?;
-                if y != self.size - 1 {
+                if y != self.width - 1 {
                     write!(f, " ")?;
                 }
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
 
         Ok(())
@@ -266,7 +656,8 @@ impl Display for HexBoard {
 
 #[cfg(test)]
 mod test {
-    use crate::common::board::{CellState, HexBoard};
+    use crate::common::board::{BoardPosition, CellState, HexBoard};
+    use crate::common::moves::Move;
     use crate::common::player::Player;
 
     #[test]
@@ -308,4 +699,207 @@ mod test {
         let winner = board.winner();
         assert_eq!(winner, None);
     }
+
+    #[test]
+    fn test_hash() {
+        let mut a = HexBoard::new(5);
+        let mut b = HexBoard::new(5);
+        assert_eq!(a.hash, b.hash);
+        assert_ne!(a.hash, HexBoard::new(6).hash);
+        assert_ne!(a.hash_with_player(Player::Red), a.hash_with_player(Player::Blue));
+
+        // same position in another move order
+        a.set(1, 2, CellState::Red);
+        a.set(3, 3, CellState::Blue);
+        b.set(3, 3, CellState::Blue);
+        b.set(0, 0, CellState::Red);
+        assert_ne!(a.hash, b.hash);
+        b.set(0, 0, CellState::Empty);
+        b.set(1, 2, CellState::Red);
+        assert_eq!(a.hash, b.hash);
+
+        let mut c = a.clone();
+        c.set(1, 2, CellState::Blue);
+        assert_ne!(a.hash, c.hash);
+        assert_eq!(a.rotate180().hash, a.rotate180().compute_hash());
+        assert_eq!(a.swap_pieces().swap_pieces().hash, a.hash);
+
+        let mut d = a.clone();
+        let expected = d.hash_after_move(8, Player::Red);
+        d.set_abs(8, CellState::Red);
+        assert_eq!(d.hash_with_player(Player::Blue), expected);
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut board = HexBoard::new(4);
+        board.fill_row(0, "_r__");
+        board.fill_row(1, "_r__");
+        board.fill_row(2, "r_b_");
+        board.fill_row(3, "r___");
+        assert!(board.is_connected((0, 1), (1, 1)));
+        assert!(board.is_connected((1, 1), (2, 0)));
+        assert!(!board.is_connected((0, 1), (2, 2)));
+        assert_eq!(board.winner(), Some(Player::Red));
+
+        board.set(1, 1, CellState::Empty);
+        assert_eq!(board.winner(), None);
+        assert!(board.is_connected((2, 0), (3, 0)));
+        assert!(!board.is_connected((0, 1), (2, 0)));
+
+        board.set(1, 1, CellState::Blue);
+        board.set(1, 0, CellState::Blue);
+        board.set(1, 2, CellState::Blue);
+        board.set(1, 3, CellState::Blue);
+        assert_eq!(board.winner(), Some(Player::Blue));
+
+        let mut copy = HexBoard::new(4);
+        copy.data = board.data.clone();
+        copy.rebuild();
+        copy.empty_count = board.empty_count;
+        assert!(copy == board);
+        assert_eq!(copy.winner(), Some(Player::Blue));
+    }
+
+    #[test]
+    fn test_unmake() {
+        let mut board = HexBoard::new(5);
+        board.fill_row(2, "rr_bb");
+        let moves = [(1, 2, CellState::Red), (2, 2, CellState::Red), (3, 2, CellState::Blue), (0, 2, CellState::Red), (4, 1, CellState::Red)];
+
+        let mut saved = Vec::new();
+        for &(x, y, cell) in moves.iter() {
+            saved.push((board.parent.clone(), board.rank.clone(), board.hash));
+            board.set(x, y, cell);
+        }
+        assert!(board.is_connected((0, 2), (2, 0)));
+
+        // taken back in stack order the groups are restored exactly, without a rebuild
+        for &(x, y, _) in moves.iter().rev() {
+            board.set(x, y, CellState::Empty);
+            let (parent, rank, hash) = saved.pop().unwrap();
+            assert_eq!(board.parent, parent);
+            assert_eq!(board.rank, rank);
+            assert_eq!(board.hash, hash);
+        }
+        assert!(board.is_connected((2, 0), (2, 1)));
+        assert_eq!(board.winner(), None);
+
+        // out of order the groups are rebuilt
+        board.set(1, 1, CellState::Red);
+        board.set(3, 3, CellState::Blue);
+        board.set(1, 1, CellState::Empty);
+        assert!(board.is_connected((2, 3), (3, 3)));
+        assert!(board.is_connected((2, 0), (2, 1)));
+    }
+
+    #[test]
+    fn test_swap_pieces() {
+        let mut board = HexBoard::new(5);
+        assert!(!board.can_swap(Player::Blue));
+
+        board.play(Move::place(1, 3), Player::Red);
+        assert!(board.can_swap(Player::Blue));
+        assert!(!board.can_swap(Player::Red));
+
+        board.play(Move::Swap, Player::Blue);
+        assert_eq!(board.get(3, 1), CellState::Blue);
+        assert_eq!(board.get(1, 3), CellState::Empty);
+        assert_eq!(board.empty_count, 24);
+        assert!(!board.can_swap(Player::Blue));
+    }
+
+    #[test]
+    fn test_rectangle() {
+        // 3 columns and 4 rows
+        let mut board = HexBoard::new_rect(3, 4);
+        assert_eq!(board.cell_count(), 12);
+        assert_eq!(board.data.len(), 12);
+        assert_ne!(board.hash, HexBoard::new_rect(4, 3).hash);
+        assert_eq!(board.get_with_padding(4, 0), CellState::Red);
+        assert_eq!(board.get_with_padding(0, 3), CellState::Blue);
+        assert_eq!(board.get_position(1, 3), BoardPosition::RightTop);
+        assert_eq!(board.get_position(4, 1), BoardPosition::RightBottom);
+
+        for x in 0..3 {
+            board.set(x, 2, CellState::Red);
+        }
+        assert_eq!(board.winner(), None);
+        board.set(3, 1, CellState::Red);
+        assert_eq!(board.winner(), Some(Player::Red));
+        assert_eq!(board.get_abs(10), CellState::Red);
+
+        let mut board = HexBoard::new_rect(3, 4);
+        board.fill_row(1, "bbb");
+        assert_eq!(board.winner(), Some(Player::Blue));
+
+        let swapped = board.swap_pieces();
+        assert_eq!((swapped.width, swapped.height), (4, 3));
+        assert_eq!(swapped.winner(), Some(Player::Red));
+
+        let rotated = board.rotate180();
+        assert_eq!(rotated.get(2, 0), CellState::Blue);
+        assert_eq!(rotated.get(1, 0), CellState::Empty);
+    }
+
+    #[test]
+    fn test_text_format() {
+        let mut board = HexBoard::new_rect(4, 3);
+        board.fill_row(0, "_r__");
+        board.fill_row(1, "b_r_");
+        board.fill_row(2, "____");
+
+        assert_eq!(board.to_text(), "_r__\nb_r_\n____");
+        assert_eq!(board.to_compact(), "1r2/b1r1/4");
+        assert_eq!(board.to_labeled_string(), [
+            "   a b c d",
+            " 1 _ r _ _ 1",
+            "  2 b _ r _ 2",
+            "   3 _ _ _ _ 3",
+            "      a b c d",
+        ].join("\n"));
+
+        for s in [board.to_text(), board.to_compact(), board.to_labeled_string()] {
+            let parsed = s.parse::<HexBoard>().unwrap();
+            assert!(parsed == board);
+            assert_eq!((parsed.width, parsed.height), (4, 3));
+        }
+        assert!("\n  . r . .\n  b . r .\n  . . . .\n".parse::<HexBoard>().unwrap() == board);
+
+        let mut board = HexBoard::new(12);
+        board.set(3, 11, CellState::Blue);
+        assert_eq!(board.to_compact().split('/').nth(3), Some("11b"));
+        assert!(board.to_compact().parse::<HexBoard>().unwrap() == board);
+        assert!(board.to_labeled_string().parse::<HexBoard>().unwrap() == board);
+
+        assert!("r_/b".parse::<HexBoard>().is_err());
+        assert!("rx".parse::<HexBoard>().is_err());
+        assert!("".parse::<HexBoard>().is_err());
+        assert!("_".repeat(26).parse::<HexBoard>().is_err());
+        assert!("26".parse::<HexBoard>().is_err());
+        assert!("99999999999999999999999".parse::<HexBoard>().is_err());
+        assert!("r1/3".parse::<HexBoard>().is_err());
+
+        // boards of one row
+        for s in ["3", "r", "1b1", "25"] {
+            let board = s.parse::<HexBoard>().unwrap();
+            assert_eq!(board.height, 1);
+            assert_eq!(board.to_compact(), s);
+            for s in [board.to_text(), board.to_compact(), board.to_labeled_string()] {
+                assert!(s.parse::<HexBoard>().unwrap() == board);
+            }
+        }
+    }
+
+    #[test]
+    fn test_serde() {
+        let board: HexBoard = "r_/_b/__".parse().unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, r#"{"width":2,"height":3,"rows":["r_","_b","__"]}"#);
+
+        let parsed: HexBoard = serde_json::from_str(&json).unwrap();
+        assert!(parsed == board);
+        assert!(parsed.winner().is_none());
+        assert!(serde_json::from_str::<HexBoard>(r#"{"width":3,"height":3,"rows":["r_","_b","__"]}"#).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,114 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// A cell of the board, `x` is the row and `y` the column, like `HexBoard::get`.
+///
+/// It is written in the standard Hex notation: the column as a letter and the row as a 1-based number,
+/// so `a1` is `(0, 0)` and `c10` is `(9, 2)`. The transposed notation of HexGui swaps the two,
+/// the letter is the row and the number is the column
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Cell {
+    pub fn new(x: usize, y: usize) -> Cell {
+        Cell { x, y }
+    }
+
+    /// the cell of an index `x * width + y`, as used by `HexBoard::get_abs`
+    pub fn from_pos(pos: usize, width: usize) -> Cell {
+        Cell::new(pos / width, pos % width)
+    }
+
+    pub fn pos(&self, width: usize) -> usize {
+        self.x * width + self.y
+    }
+
+    pub fn is_in_board(&self, width: usize, height: usize) -> bool {
+        self.x < height && self.y < width
+    }
+
+    pub fn transpose(&self) -> Cell {
+        Cell::new(self.y, self.x)
+    }
+
+    /// parse a cell of a board with `width` columns and `height` rows
+    pub fn parse(s: &str, width: usize, height: usize) -> Result<Cell, String> {
+        let cell = s.parse::<Cell>()?;
+        if !cell.is_in_board(width, height) {
+            return Err(format!("coordinate out of board: {}", s.to_lowercase()));
+        }
+        Ok(cell)
+    }
+
+    /// parse a cell written in the transposed notation
+    pub fn parse_transposed(s: &str, width: usize, height: usize) -> Result<Cell, String> {
+        let cell = s.parse::<Cell>()?.transpose();
+        if !cell.is_in_board(width, height) {
+            return Err(format!("coordinate out of board: {}", s.to_lowercase()));
+        }
+        Ok(cell)
+    }
+
+    pub fn to_transposed_string(&self) -> String {
+        self.transpose().to_string()
+    }
+}
+
+/// parse a cell without checking the board size
+impl FromStr for Cell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let mut chars = s.chars();
+        let column = match chars.next() {
+            Some(c) if c.is_ascii_lowercase() => c as usize - 'a' as usize,
+            _ => return Err(format!("invalid coordinate: {}", s))
+        };
+        let row = chars.as_str().parse::<usize>().map_err(|_| format!("invalid coordinate: {}", s))?;
+        if row == 0 {
+            return Err(format!("coordinate out of board: {}", s));
+        }
+
+        Ok(Cell::new(row - 1, column))
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", (b'a' + self.y as u8) as char, self.x + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::cell::Cell;
+
+    #[test]
+    fn test_notation() {
+        assert_eq!(Cell::parse("a1", 11, 11), Ok(Cell::new(0, 0)));
+        assert_eq!(Cell::parse("K11", 11, 11), Ok(Cell::new(10, 10)));
+        assert_eq!(Cell::parse("c10", 11, 11), Ok(Cell::new(9, 2)));
+        assert!(Cell::parse("l1", 11, 11).is_err());
+        assert!(Cell::parse("a0", 11, 11).is_err());
+        assert!(Cell::parse("a", 11, 11).is_err());
+        assert!(Cell::parse("1a", 11, 11).is_err());
+        assert_eq!(Cell::parse("e7", 5, 7), Ok(Cell::new(6, 4)));
+        assert!(Cell::parse("f1", 5, 7).is_err());
+        assert!(Cell::parse("a8", 5, 7).is_err());
+        assert_eq!("s19".parse::<Cell>(), Ok(Cell::new(18, 18)));
+        assert_eq!(Cell::new(9, 2).to_string(), "c10");
+
+        assert_eq!(Cell::parse_transposed("c10", 11, 11), Ok(Cell::new(2, 9)));
+        assert_eq!(Cell::parse_transposed("g1", 5, 7), Ok(Cell::new(6, 0)));
+        assert!(Cell::parse_transposed("e7", 5, 7).is_err());
+        assert_eq!(Cell::new(2, 9).to_transposed_string(), "c10");
+
+        assert_eq!(Cell::from_pos(13, 5), Cell::new(2, 3));
+        assert_eq!(Cell::new(2, 3).pos(5), 13);
+    }
+}
@@ -0,0 +1,240 @@
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Shl, Shr, Sub};
+use crate::common::board::{CellState, HexBoard};
+use crate::common::constants::MAX_SIZE;
+
+pub const WORDS: usize = (MAX_SIZE * MAX_SIZE).div_ceil(64);
+
+/// A set of cells, indexed by `x * width + y` like `HexBoard::get_abs`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub struct CellSet {
+    pub data: [u64; WORDS],
+}
+
+impl CellSet {
+    pub fn new() -> CellSet {
+        CellSet {
+            data: [0; WORDS],
+        }
+    }
+
+    /// the first `count` cells
+    pub fn full(count: usize) -> CellSet {
+        let mut result = CellSet::new();
+        for i in 0..WORDS {
+            if count >= (i + 1) * 64 {
+                result.data[i] = u64::MAX;
+            } else if count > i * 64 {
+                result.data[i] = (1 << (count - i * 64)) - 1;
+            }
+        }
+        result
+    }
+
+    pub fn insert(&mut self, pos: usize) {
+        self.data[pos >> 6] |= 1 << (pos & 63);
+    }
+
+    pub fn remove(&mut self, pos: usize) {
+        self.data[pos >> 6] &= !(1 << (pos & 63));
+    }
+
+    pub fn contains(&self, pos: usize) -> bool {
+        self.data[pos >> 6] >> (pos & 63) & 1 == 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.iter().all(|&x| x == 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.iter().map(|x| x.count_ones() as usize).sum()
+    }
+
+    pub fn is_disjoint(&self, other: &CellSet) -> bool {
+        self.data.iter().zip(other.data.iter()).all(|(a, b)| a & b == 0)
+    }
+
+    pub fn is_subset(&self, other: &CellSet) -> bool {
+        self.data.iter().zip(other.data.iter()).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn iter(&self) -> CellSetIter<'_> {
+        CellSetIter {
+            set: self,
+            word: 0,
+            bits: self.data[0],
+        }
+    }
+}
+
+impl FromIterator<usize> for CellSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut result = CellSet::new();
+        for pos in iter {
+            result.insert(pos);
+        }
+        result
+    }
+}
+
+pub struct CellSetIter<'a> {
+    set: &'a CellSet,
+    word: usize,
+    bits: u64,
+}
+
+impl<'a> Iterator for CellSetIter<'a> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.bits == 0 {
+            self.word += 1;
+            if self.word >= WORDS {
+                return None;
+            }
+            self.bits = self.set.data[self.word];
+        }
+
+        let bit = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        Some(self.word * 64 + bit)
+    }
+}
+
+impl BitOr for CellSet {
+    type Output = CellSet;
+
+    fn bitor(mut self, rhs: CellSet) -> CellSet {
+        self |= rhs;
+        self
+    }
+}
+
+impl BitOrAssign for CellSet {
+    fn bitor_assign(&mut self, rhs: CellSet) {
+        for i in 0..WORDS {
+            self.data[i] |= rhs.data[i];
+        }
+    }
+}
+
+impl BitAnd for CellSet {
+    type Output = CellSet;
+
+    fn bitand(mut self, rhs: CellSet) -> CellSet {
+        self &= rhs;
+        self
+    }
+}
+
+impl BitAndAssign for CellSet {
+    fn bitand_assign(&mut self, rhs: CellSet) {
+        for i in 0..WORDS {
+            self.data[i] &= rhs.data[i];
+        }
+    }
+}
+
+impl Sub for CellSet {
+    type Output = CellSet;
+
+    fn sub(mut self, rhs: CellSet) -> CellSet {
+        for i in 0..WORDS {
+            self.data[i] &= !rhs.data[i];
+        }
+        self
+    }
+}
+
+/// moves every cell `n` positions up, cells past the last word are dropped
+impl Shl<usize> for CellSet {
+    type Output = CellSet;
+
+    fn shl(self, n: usize) -> CellSet {
+        let mut result = CellSet::new();
+        let (words, bits) = (n / 64, n % 64);
+        for i in (words..WORDS).rev() {
+            let mut value = self.data[i - words] << bits;
+            if bits > 0 && i > words {
+                value |= self.data[i - words - 1] >> (64 - bits);
+            }
+            result.data[i] = value;
+        }
+        result
+    }
+}
+
+/// moves every cell `n` positions down, cells below 0 are dropped
+impl Shr<usize> for CellSet {
+    type Output = CellSet;
+
+    fn shr(self, n: usize) -> CellSet {
+        let mut result = CellSet::new();
+        let (words, bits) = (n / 64, n % 64);
+        for i in 0..WORDS.saturating_sub(words) {
+            let mut value = self.data[i + words] >> bits;
+            if bits > 0 && i + words + 1 < WORDS {
+                value |= self.data[i + words + 1] << (64 - bits);
+            }
+            result.data[i] = value;
+        }
+        result
+    }
+}
+
+/// complement over all `MAX_SIZE * MAX_SIZE` cells, mask with `CellSet::full` for smaller boards
+impl Not for CellSet {
+    type Output = CellSet;
+
+    fn not(mut self) -> CellSet {
+        for i in 0..WORDS {
+            self.data[i] = !self.data[i];
+        }
+        self & CellSet::full(MAX_SIZE * MAX_SIZE)
+    }
+}
+
+impl HexBoard {
+    /// the cells of red and the cells of blue
+    pub fn cell_sets(&self) -> (CellSet, CellSet) {
+        let mut red = CellSet::new();
+        let mut blue = CellSet::new();
+        for i in 0..self.cell_count() {
+            match self.get_abs(i) {
+                CellState::Red => red.insert(i),
+                CellState::Blue => blue.insert(i),
+                CellState::Empty => {}
+            }
+        }
+        (red, blue)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::cell_set::CellSet;
+    use crate::common::constants::MAX_SIZE;
+
+    #[test]
+    fn test_cell_set() {
+        let a: CellSet = [1, 64, 200].into_iter().collect();
+        let b: CellSet = [64, 360].into_iter().collect();
+
+        assert_eq!(a.len(), 3);
+        assert!(a.contains(200) && !a.contains(2));
+        assert_eq!((a | b).iter().collect::<Vec<_>>(), vec![1, 64, 200, 360]);
+        assert_eq!((a & b).iter().collect::<Vec<_>>(), vec![64]);
+        assert_eq!((a - b).iter().collect::<Vec<_>>(), vec![1, 200]);
+        assert!(!a.is_disjoint(&b));
+        assert!((a & b).is_subset(&a));
+        assert_eq!(CellSet::full(70).len(), 70);
+        assert_eq!((!a).len(), MAX_SIZE * MAX_SIZE - 3);
+        assert!(CellSet::new().iter().next().is_none());
+
+        assert_eq!((a << 5).iter().collect::<Vec<_>>(), vec![6, 69, 205]);
+        assert_eq!((a << 130).iter().collect::<Vec<_>>(), vec![131, 194, 330]);
+        assert_eq!((a >> 2).iter().collect::<Vec<_>>(), vec![62, 198]);
+        assert_eq!((b >> 100).iter().collect::<Vec<_>>(), vec![260]);
+        assert_eq!(b << 600, CellSet::new());
+    }
+}
@@ -1,4 +1,4 @@
-pub const MAX_SIZE: usize = 19;
+pub const MAX_SIZE: usize = 25;
 
 pub const DIS1: [[i32; 2]; 6] = [[0, -1], [-1, 0], [-1, 1], [0, 1], [1, 0], [1, -1]];
 
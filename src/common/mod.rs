@@ -1,3 +1,7 @@
 pub mod board;
 pub mod player;
 pub mod constants;
+pub mod moves;
+pub mod cell;
+pub mod cell_set;
+pub mod bit_board;
@@ -0,0 +1,90 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::common::cell::Cell;
+
+/// A move in the game. `Swap` is only legal as the second move of the game:
+/// the first stone is transposed to the mirrored cell and changes color.
+/// `Resign` ends the game, the opponent wins.
+///
+/// Moves are written like in HTP: a cell, `swap-pieces` and `resign`
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum Move {
+    Place(Cell),
+    Swap,
+    Resign,
+}
+
+impl Move {
+    pub fn place(x: usize, y: usize) -> Move {
+        Move::Place(Cell::new(x, y))
+    }
+
+    /// parse a move of a board with `width` columns and `height` rows
+    pub fn parse(s: &str, width: usize, height: usize) -> Result<Move, String> {
+        match s.parse::<Move>()? {
+            Move::Place(_) => Ok(Move::Place(Cell::parse(s, width, height)?)),
+            mov => Ok(mov),
+        }
+    }
+
+    /// parse a move with the cell in the transposed notation
+    pub fn parse_transposed(s: &str, width: usize, height: usize) -> Result<Move, String> {
+        match s.parse::<Move>()? {
+            Move::Place(_) => Ok(Move::Place(Cell::parse_transposed(s, width, height)?)),
+            mov => Ok(mov),
+        }
+    }
+
+    pub fn to_transposed_string(&self) -> String {
+        match self {
+            Move::Place(cell) => cell.to_transposed_string(),
+            mov => mov.to_string(),
+        }
+    }
+}
+
+/// parse a move without checking the board size, `swap` is accepted for `swap-pieces`
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "swap-pieces" | "swap" => Ok(Move::Swap),
+            "resign" => Ok(Move::Resign),
+            _ => Ok(Move::Place(s.parse()?)),
+        }
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Move::Place(cell) => write!(f, "{}", cell),
+            Move::Swap => write!(f, "swap-pieces"),
+            Move::Resign => write!(f, "resign"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::cell::Cell;
+    use crate::common::moves::Move;
+
+    #[test]
+    fn test_notation() {
+        assert_eq!(Move::parse("b3", 5, 5), Ok(Move::place(2, 1)));
+        assert_eq!(Move::parse("swap-pieces", 5, 5), Ok(Move::Swap));
+        assert_eq!(Move::parse("SWAP", 5, 5), Ok(Move::Swap));
+        assert_eq!(Move::parse("resign", 5, 5), Ok(Move::Resign));
+        assert!(Move::parse("f1", 5, 5).is_err());
+        assert!(Move::parse("pass", 5, 5).is_err());
+        assert_eq!(Move::parse_transposed("b3", 5, 5), Ok(Move::Place(Cell::new(1, 2))));
+
+        for mov in [Move::place(4, 0), Move::Swap, Move::Resign] {
+            assert_eq!(mov.to_string().parse::<Move>(), Ok(mov));
+            assert_eq!(Move::parse_transposed(&mov.to_transposed_string(), 5, 5), Ok(mov));
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::common::board::CellState;
-use serde::{Serialize};
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
 pub enum Player {
     Red,
     Blue
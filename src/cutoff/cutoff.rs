@@ -1,6 +1,12 @@
 use crate::common::board::HexBoard;
 use crate::common::player::Player;
 
-pub trait Cutoff {
+pub trait Cutoff: Send + Sync {
     fn cutoff(&self, board: &HexBoard, next_player: Player, last_move: Option<(usize, usize)>, out: &mut [bool]);
-}
\ No newline at end of file
+
+    /// whether the cells left always contain a winning move when there is one,
+    /// only then a position is lost when all the moves left are lost
+    fn is_sound(&self) -> bool {
+        false
+    }
+}
@@ -1,21 +1,23 @@
 use crate::common::board::{CellState, HexBoard};
-use crate::common::constants::MAX_SIZE;
 use crate::common::player::Player;
 use crate::cutoff::cutoff::Cutoff;
 use crate::inferior_cell::inferior_cell::{get_inferior1, get_inferior2, InferiorCellType};
 
+/// Cut dead cells and cells captured by the player to move.
+/// The dead and captured patterns are not exact, they cut winning moves on small boards,
+/// so the fill-in loop is switched off and the cutoff is not sound
 pub struct InferiorCellCutoff;
 
 impl Cutoff for InferiorCellCutoff {
     fn cutoff(&self, board: &HexBoard, next_player: Player, _last_move: Option<(usize, usize)>, out: &mut [bool]) {
-        let size = board.size;
+        let width = board.width;
         let mut board = board.clone();
 
         let mut flag = false;
         while flag {
             flag = false;
 
-            let mut temp = [(0, 0); MAX_SIZE * MAX_SIZE];
+            let mut temp = vec![(0, 0); board.cell_count()];
             let mut out_count = 0;
             flag = flag || get_inferior1(&board, InferiorCellType::Dead1, |x, y| {
                 temp[out_count] = (x, y);
@@ -24,10 +26,10 @@ impl Cutoff for InferiorCellCutoff {
             for i in 0..out_count {
                 let (x, y) = temp[i];
                 board.set(x, y, CellState::Red);
-                out[x * size + y] = true;
+                out[x * width + y] = true;
             }
 
-            let mut temp = [(0, 0); MAX_SIZE * MAX_SIZE];
+            let mut temp = vec![(0, 0); board.cell_count()];
             let mut out_count = 0;
             flag = flag || get_inferior2(&board, next_player, InferiorCellType::Captured2, |x, y| {
                 temp[out_count] = (x, y);
@@ -36,14 +38,14 @@ impl Cutoff for InferiorCellCutoff {
             for i in 0..out_count {
                 let (x, y) = temp[i];
                 board.set(x, y, next_player.to_cell());
-                out[x * size + y] = true;
+                out[x * width + y] = true;
             }
         }
         // get_inferior2(&board, next_player, InferiorCellType::Inferior2, |x, y| {
-        //     out[x * size + y] = true;
+        //     out[x * width + y] = true;
         // });
         // get_inferior1(&board, InferiorCellType::Inferior1, |x, y| {
-        //     out[x * size + y] = true;
+        //     out[x * width + y] = true;
         // });
 
     }
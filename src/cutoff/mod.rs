@@ -2,3 +2,4 @@ pub mod cutoff;
 pub mod inferior_cell_cutoff;
 pub mod two_distance_cutoff;
 pub mod pattern_cutoff;
+pub mod mustplay_cutoff;
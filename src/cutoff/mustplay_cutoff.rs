@@ -0,0 +1,63 @@
+use crate::common::board::{CellState, HexBoard};
+use crate::common::player::Player;
+use crate::cutoff::cutoff::Cutoff;
+use crate::vc::vc::VcSet;
+
+/// Keep only the cells that can stop the virtual connections of the opponent between their edges.
+/// When no cell can, every cell is cut and the node is lost for the player to move
+pub struct MustplayCutoff;
+
+impl Cutoff for MustplayCutoff {
+    fn cutoff(&self, board: &HexBoard, next_player: Player, _last_move: Option<(usize, usize)>, out: &mut [bool]) {
+        let mustplay = match VcSet::compute(board, next_player.reverse()).mustplay() {
+            Some(x) => x,
+            None => return
+        };
+
+        for (i, cut) in out.iter_mut().enumerate().take(board.cell_count()) {
+            if board.get_abs(i) == CellState::Empty && !mustplay.contains(i) {
+                *cut = true;
+            }
+        }
+    }
+
+    fn is_sound(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::constants::MAX_SIZE;
+    use crate::common::player::Player;
+    use crate::cutoff::cutoff::Cutoff;
+    use crate::cutoff::mustplay_cutoff::MustplayCutoff;
+
+    #[test]
+    fn test_mustplay() {
+        // red wins with (3, 0) unless blue takes it
+        let mut board = HexBoard::new(4);
+        board.set(0, 1, CellState::Red);
+        board.set(1, 1, CellState::Red);
+        board.set(2, 1, CellState::Red);
+        board.set(3, 1, CellState::Blue);
+        board.set(2, 2, CellState::Blue);
+        let mut out = [false; MAX_SIZE * MAX_SIZE];
+        MustplayCutoff.cutoff(&board, Player::Blue, None, &mut out);
+        let kept = (0..16).filter(|&i| board.get_abs(i) == CellState::Empty && !out[i]).collect::<Vec<_>>();
+        assert_eq!(kept, vec![12]);
+
+        // red holds the center and wins whatever blue plays
+        let mut board = HexBoard::new(3);
+        board.set(1, 1, CellState::Red);
+        let mut out = [false; MAX_SIZE * MAX_SIZE];
+        MustplayCutoff.cutoff(&board, Player::Blue, None, &mut out);
+        assert!((0..9).all(|i| i == 4 || out[i]));
+
+        // nothing to stop on an empty board
+        let mut out = [false; MAX_SIZE * MAX_SIZE];
+        MustplayCutoff.cutoff(&HexBoard::new(9), Player::Red, None, &mut out);
+        assert!(out.iter().all(|&x| !x));
+    }
+}
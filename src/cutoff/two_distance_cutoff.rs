@@ -1,6 +1,5 @@
 use itertools::Itertools;
 use crate::common::board::HexBoard;
-use crate::common::constants::MAX_SIZE;
 use crate::common::player::Player;
 use crate::cutoff::cutoff::Cutoff;
 use crate::two_distance::two_distance::TwoDistanceStart;
@@ -11,10 +10,9 @@ pub struct TwoDistanceCutoff {
 
 impl Cutoff for TwoDistanceCutoff {
     fn cutoff(&self, board: &HexBoard, next_player: Player, _last_move: Option<(usize, usize)>, out: &mut [bool]) {
-        let mut dis1 = [0; MAX_SIZE * MAX_SIZE];
-        let mut dis2 = [0; MAX_SIZE * MAX_SIZE];
-        let size = board.size;
-        let ss = size * size;
+        let ss = board.cell_count();
+        let mut dis1 = vec![0; ss];
+        let mut dis2 = vec![0; ss];
 
         if next_player == Player::Red {
             board.two_distance(TwoDistanceStart::LeftTop, &mut dis1[..]);
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use crate::common::board::{CellState, HexBoard};
+use crate::common::moves::Move;
+use crate::common::player::Player;
+
+/// A game: the starting position, the moves played from it and the current position.
+///
+/// Undone moves are kept until a different move is played, so they can be redone.
+/// Moves are usually played by the player to move, but `play_as` lets a protocol place
+/// stones of either color, the player to move is always the opponent of the last mover
+#[derive(Clone)]
+pub struct Game {
+    /// the current position
+    pub board: HexBoard,
+    /// whether the second player may answer the first move with `Move::Swap`
+    pub allow_swap: bool,
+    pub red_name: Option<String>,
+    pub blue_name: Option<String>,
+
+    initial: HexBoard,
+    first_player: Player,
+    moves: Vec<(Player, Move)>,
+    ply: usize,
+    /// comments of the positions, by the number of moves played to reach them
+    comments: BTreeMap<usize, String>,
+}
+
+impl Game {
+    /// a game on an empty square board, red moves first
+    pub fn new(size: usize) -> Game {
+        Game::from_position(HexBoard::new(size), Player::Red)
+    }
+
+    pub fn new_rect(width: usize, height: usize) -> Game {
+        Game::from_position(HexBoard::new_rect(width, height), Player::Red)
+    }
+
+    /// a game starting from `board` with `next_player` to move
+    pub fn from_position(board: HexBoard, next_player: Player) -> Game {
+        Game {
+            board: board.clone(),
+            allow_swap: false,
+            red_name: None,
+            blue_name: None,
+            initial: board,
+            first_player: next_player,
+            moves: Vec::new(),
+            ply: 0,
+            comments: BTreeMap::new(),
+        }
+    }
+
+    pub fn initial_board(&self) -> &HexBoard {
+        &self.initial
+    }
+
+    pub fn first_player(&self) -> Player {
+        self.first_player
+    }
+
+    /// number of moves played to reach the current position
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// the moves played to reach the current position
+    pub fn history(&self) -> &[(Player, Move)] {
+        &self.moves[..self.ply]
+    }
+
+    /// the whole record, including the moves that were undone and can be redone
+    pub fn record(&self) -> &[(Player, Move)] {
+        &self.moves
+    }
+
+    pub fn next_player(&self) -> Player {
+        match self.history().last() {
+            Some(&(player, _)) => player.reverse(),
+            None => self.first_player
+        }
+    }
+
+    /// the player who connected their sides, or the opponent of a player who resigned
+    pub fn winner(&self) -> Option<Player> {
+        match self.history().last() {
+            Some(&(player, Move::Resign)) => Some(player.reverse()),
+            _ => self.board.winner()
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    /// whether `player` may swap now: the swap rule is on, only the first move is played,
+    /// it placed a stone and it was played by the opponent
+    pub fn can_swap(&self, player: Player) -> bool {
+        let history = self.history();
+        self.allow_swap
+            && history.len() == 1
+            && history[0].0 != player
+            && matches!(history[0].1, Move::Place(..))
+    }
+
+    /// check that `player` may play `mov` in the current position
+    pub fn check_move(&self, player: Player, mov: Move) -> Result<(), String> {
+        if self.is_over() {
+            return Err(String::from("game is over"));
+        }
+
+        match mov {
+            Move::Place(cell) => {
+                if !cell.is_in_board(self.board.width, self.board.height) {
+                    return Err(format!("cell out of board: {}", cell));
+                }
+                if !self.board.is_empty(cell.x, cell.y) {
+                    return Err(format!("cell is occupied: {}", cell));
+                }
+            },
+            Move::Swap => if !self.can_swap(player) {
+                return Err(String::from("swap is not allowed"));
+            },
+            Move::Resign => {}
+        }
+
+        Ok(())
+    }
+
+    /// play a move of the player to move
+    pub fn play(&mut self, mov: Move) -> Result<(), String> {
+        self.play_as(self.next_player(), mov)
+    }
+
+    /// play a move of `player`, dropping the moves that could be redone
+    pub fn play_as(&mut self, player: Player, mov: Move) -> Result<(), String> {
+        self.check_move(player, mov)?;
+
+        self.board.play(mov, player);
+        self.moves.truncate(self.ply);
+        let ply = self.ply;
+        self.comments.retain(|&x, _| x <= ply);
+        self.moves.push((player, mov));
+        self.ply += 1;
+        Ok(())
+    }
+
+    /// take back the last move, None at the start of the game
+    pub fn undo(&mut self) -> Option<(Player, Move)> {
+        if self.ply == 0 {
+            return None;
+        }
+
+        self.ply -= 1;
+        let (player, mov) = self.moves[self.ply];
+        match mov {
+            Move::Place(cell) => self.board.set(cell.x, cell.y, CellState::Empty),
+            // mirroring twice gives back the original position
+            Move::Swap => self.board = self.board.swap_pieces(),
+            Move::Resign => {}
+        }
+
+        Some((player, mov))
+    }
+
+    /// play again the last undone move, None when there is none
+    pub fn redo(&mut self) -> Option<(Player, Move)> {
+        let (player, mov) = *self.moves.get(self.ply)?;
+        self.board.play(mov, player);
+        self.ply += 1;
+
+        Some((player, mov))
+    }
+
+    /// the position after the first `ply` moves of the record
+    pub fn board_at(&self, ply: usize) -> Result<HexBoard, String> {
+        if ply > self.moves.len() {
+            return Err(format!("the game has only {} moves", self.moves.len()));
+        }
+
+        let mut board = self.initial.clone();
+        for &(player, mov) in self.moves[..ply].iter() {
+            board.play(mov, player);
+        }
+        Ok(board)
+    }
+
+    /// make the position after the first `ply` moves of the record the current one,
+    /// the later moves can still be redone
+    pub fn goto(&mut self, ply: usize) -> Result<(), String> {
+        self.board = self.board_at(ply)?;
+        self.ply = ply;
+        Ok(())
+    }
+
+    /// start again from the initial position and forget every move
+    pub fn clear(&mut self) {
+        self.board = self.initial.clone();
+        self.moves.clear();
+        self.ply = 0;
+        self.comments.retain(|&x, _| x == 0);
+    }
+
+    /// the comment of the position after the first `ply` moves of the record
+    pub fn comment_at(&self, ply: usize) -> Option<&str> {
+        self.comments.get(&ply).map(|x| x.as_str())
+    }
+
+    /// comment the current position, it is dropped with the moves leading to it
+    pub fn set_comment(&mut self, comment: &str) {
+        self.comments.insert(self.ply, String::from(comment));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::CellState;
+    use crate::common::moves::Move;
+    use crate::common::player::Player;
+    use crate::game::game::Game;
+
+    #[test]
+    fn test_play_undo_redo() {
+        let mut game = Game::new(3);
+        assert_eq!(game.next_player(), Player::Red);
+
+        game.play(Move::place(0, 0)).unwrap();
+        game.play(Move::place(1, 1)).unwrap();
+        assert_eq!(game.next_player(), Player::Red);
+        assert!(game.play(Move::place(1, 1)).is_err());
+        assert!(game.play(Move::place(3, 0)).is_err());
+
+        assert_eq!(game.undo(), Some((Player::Blue, Move::place(1, 1))));
+        assert_eq!(game.board.get(1, 1), CellState::Empty);
+        assert_eq!(game.next_player(), Player::Blue);
+        assert_eq!(game.record().len(), 2);
+
+        assert_eq!(game.redo(), Some((Player::Blue, Move::place(1, 1))));
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.board.get(1, 1), CellState::Blue);
+
+        game.set_comment("blue blocks");
+        game.undo();
+        game.set_comment("red opens");
+        game.play(Move::place(2, 2)).unwrap();
+        assert_eq!(game.record().len(), 2);
+        assert_eq!(game.redo(), None);
+        assert_eq!(game.comment_at(1), Some("red opens"));
+        assert_eq!(game.comment_at(2), None);
+
+        game.undo();
+        game.undo();
+        assert_eq!(game.undo(), None);
+        assert!(game.board == *game.initial_board());
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut game = Game::new(5);
+        game.play(Move::place(1, 3)).unwrap();
+        assert!(game.play(Move::Swap).is_err());
+
+        game.allow_swap = true;
+        game.play(Move::Swap).unwrap();
+        assert_eq!(game.board.get(3, 1), CellState::Blue);
+        assert_eq!(game.next_player(), Player::Red);
+        assert!(game.play(Move::Swap).is_err());
+
+        game.undo();
+        assert_eq!(game.board.get(1, 3), CellState::Red);
+        assert_eq!(game.board.get(3, 1), CellState::Empty);
+    }
+
+    #[test]
+    fn test_result_and_replay() {
+        let mut game = Game::new(2);
+        game.play(Move::place(0, 0)).unwrap();
+        game.play(Move::place(0, 1)).unwrap();
+        game.play(Move::place(1, 0)).unwrap();
+        assert_eq!(game.winner(), Some(Player::Red));
+        assert!(game.play(Move::place(1, 1)).is_err());
+
+        let board = game.board_at(1).unwrap();
+        assert_eq!(board.empty_count, 3);
+        assert!(game.board_at(4).is_err());
+
+        game.goto(2).unwrap();
+        assert_eq!(game.ply(), 2);
+        assert!(!game.is_over());
+        game.goto(3).unwrap();
+        assert!(game.is_over());
+
+        game.play_as(Player::Blue, Move::place(1, 1)).unwrap_err();
+        game.goto(1).unwrap();
+        game.play(Move::Resign).unwrap();
+        assert_eq!(game.winner(), Some(Player::Red));
+        assert!(game.play(Move::place(1, 1)).is_err());
+        game.undo();
+        assert_eq!(game.winner(), None);
+
+        game.goto(0).unwrap();
+        game.play_as(Player::Blue, Move::place(1, 1)).unwrap();
+        assert_eq!(game.next_player(), Player::Red);
+        assert_eq!(game.record().len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
+use crate::common::bit_board::BitBoard;
 use crate::common::board::{CellState, HexBoard};
 use lazy_static::lazy_static;
-use crate::common::constants::{DIS1, DIS2_DIAG, DIS2_H, DIS2_V, MAX_SIZE};
+use crate::common::constants::{DIS1, DIS2_DIAG, DIS2_H, DIS2_V};
 use crate::common::player::Player;
 use crate::inferior_cell::pattern::PatternDict;
 
@@ -8,48 +9,63 @@ lazy_static! {
     static ref PATTERNS_DICT: PatternDict = PatternDict::new();
 }
 
-impl HexBoard {
-    pub fn get_encoding1(&self, x: usize, y: usize) -> usize {
-        let mut result = 0;
+/// two bits per cell around (x, y): 1 for red, 2 for blue, 0 for empty
+fn encode(x: usize, y: usize, dis: &[[i32; 2]], get: impl Fn(i32, i32) -> CellState) -> usize {
+    let mut result = 0;
+
+    for d in dis.iter() {
+        let cell = get(x as i32 + d[0], y as i32 + d[1]);
+        if cell == CellState::Red {
+            result = (result << 2) | 1;
+        } else if cell == CellState::Blue {
+            result = (result << 2) | 2;
+        } else {
+            result <<= 2;
+        }
+    }
 
-        for i in 0..6 {
-            let nx = x as i32 + DIS1[i][0];
-            let ny = y as i32 + DIS1[i][1];
+    result
+}
 
-            let cell = self.get_with_padding(nx, ny);
-            if cell == CellState::Red {
-                result = (result << 2) | 1;
-            } else if cell == CellState::Blue {
-                result = (result << 2) | 2;
-            } else {
-                result <<= 2;
-            }
-        }
+fn encoding2_dis(direction: char) -> &'static [[i32; 2]; 8] {
+    if direction == 'v' { &DIS2_V } else if direction == 'h' { &DIS2_H } else if direction == 'd' { &DIS2_DIAG } else {
+        panic!("direction not found");
+    }
+}
 
-        result
+/// like `encode` for every cell at once, one pair of shifted stone sets per neighbor
+fn encode_all(board: &BitBoard, dis: &[[i32; 2]]) -> Vec<usize> {
+    let mut result = vec![0; board.width * board.height];
+
+    for d in dis.iter() {
+        let (red, blue) = board.shifted_stones(d[0], d[1]);
+        for (pos, code) in result.iter_mut().enumerate() {
+            *code = (*code << 2) | red.contains(pos) as usize | (blue.contains(pos) as usize) << 1;
+        }
     }
 
-    pub fn get_encoding2(&self, x: usize, y: usize, direction: char) -> usize {
-        let mut result = 0;
+    result
+}
 
-        let dis = if direction == 'v' { &DIS2_V } else if direction == 'h' { &DIS2_H } else if direction == 'd' { &DIS2_DIAG } else {
-            panic!("direction not found");
-        };
-        for i in 0..8 {
-            let nx = x as i32 + dis[i][0];
-            let ny = y as i32 + dis[i][1];
+impl BitBoard {
+    /// `get_encoding1` of every cell, indexed by position
+    pub fn encodings1(&self) -> Vec<usize> {
+        encode_all(self, &DIS1)
+    }
 
-            let cell = self.get_with_padding(nx, ny);
-            if cell == CellState::Red {
-                result = (result << 2) | 1;
-            } else if cell == CellState::Blue {
-                result = (result << 2) | 2;
-            } else {
-                result <<= 2;
-            }
-        }
+    /// `get_encoding2` of every cell, indexed by position
+    pub fn encodings2(&self, direction: char) -> Vec<usize> {
+        encode_all(self, encoding2_dis(direction))
+    }
+}
 
-        result
+impl HexBoard {
+    pub fn get_encoding1(&self, x: usize, y: usize) -> usize {
+        encode(x, y, &DIS1, |x, y| self.get_with_padding(x, y))
+    }
+
+    pub fn get_encoding2(&self, x: usize, y: usize, direction: char) -> usize {
+        encode(x, y, encoding2_dis(direction), |x, y| self.get_with_padding(x, y))
     }
 
     pub fn is_vacant2(&self, x: usize, y: usize, direction: char) -> bool {
@@ -87,21 +103,22 @@ pub fn get_inferior1<T: FnMut(usize, usize) -> ()>(board: &HexBoard, t: Inferior
         InferiorCellType::Inferior1 => &PATTERNS_DICT.inferior1,
         _ => unreachable!()
     };
-    let size = board.size;
+    let (width, height) = (board.width, board.height);
 
-    let mut temp = [[false; MAX_SIZE]; MAX_SIZE];
+    let encodings = BitBoard::from(board).encodings1();
+    let mut temp = vec![vec![false; width]; height];
     let mut flag = false;
-    for x in 0..board.size {
-        for y in 0..board.size {
+    for x in 0..height {
+        for y in 0..width {
             if board.is_empty(x, y) {
-                let encoding = board.get_encoding1(x, y);
+                let encoding = encodings[x * width + y];
                 let (test_result, critical_index) = p.patterns_map[encoding];
                 if test_result {
                     if critical_index < usize::MAX {
                         assert_eq!(t, InferiorCellType::Inferior1);
                         let cx = x as i32 + DIS1[critical_index][0];
                         let cy = y as i32 + DIS1[critical_index][1];
-                        if cx >= 0 && cx < size as i32 && cy >= 0 && cy < size as i32 {
+                        if cx >= 0 && cx < height as i32 && cy >= 0 && cy < width as i32 {
                             let (cx, cy) = (cx as usize, cy as usize);
                             flag = true;
                             temp[x][y] = true;
@@ -116,8 +133,8 @@ pub fn get_inferior1<T: FnMut(usize, usize) -> ()>(board: &HexBoard, t: Inferior
         }
     }
 
-    for x in 0..size {
-        for y in 0..size {
+    for x in 0..height {
+        for y in 0..width {
             if temp[x][y] {
                 action(x, y);
             }
@@ -137,19 +154,21 @@ pub fn get_inferior2<T: FnMut(usize, usize) -> ()>(board: &HexBoard, player: Pla
         },
         _ => unreachable!()
     };
-    let size = board.size as i32;
+    let (width, height) = (board.width as i32, board.height as i32);
 
-    let mut temp = [[false; MAX_SIZE]; MAX_SIZE];
+    let bit_board = BitBoard::from(board);
+    let encodings = ['v', 'h', 'd'].map(|d| bit_board.encodings2(d));
+    let mut temp = vec![vec![false; board.width]; board.height];
     // let mut vis = [[false; MAX_SIZE]; MAX_SIZE];
-    let mut critical = [[false; MAX_SIZE]; MAX_SIZE];
+    let mut critical = vec![vec![false; board.width]; board.height];
     let mut flag = false;
-    for x in 0..board.size {
-        for y in 0..board.size {
+    for x in 0..board.height {
+        for y in 0..board.width {
             if critical[x][y] {
                 continue;
             }
 
-            for d in ['v', 'h', 'd'] {
+            for (i, d) in ['v', 'h', 'd'].into_iter().enumerate() {
                 let (other_x, other_y) = if d == 'v' {
                     (x as i32 + 1, y as i32)
                 } else if d == 'h' {
@@ -158,9 +177,9 @@ pub fn get_inferior2<T: FnMut(usize, usize) -> ()>(board: &HexBoard, player: Pla
                     (x as i32 + 1, y as i32 - 1)
                 };
 
-                // if other_x >= 0 && other_x < size && other_y >= 0 && other_y < size && board.is_vacant2(x, y, d) {
-                if other_x >= 0 && other_x < size && other_y >= 0 && other_y < size && board.is_vacant2(x, y, d) && !critical[other_x as usize][other_y as usize] {
-                    let encoding = board.get_encoding2(x, y, d);
+                // if other_x >= 0 && other_x < height && other_y >= 0 && other_y < width && board.is_vacant2(x, y, d) {
+                if other_x >= 0 && other_x < height && other_y >= 0 && other_y < width && board.is_vacant2(x, y, d) && !critical[other_x as usize][other_y as usize] {
+                    let encoding = encodings[i][x * board.width + y];
                     let (test_result, critical_index) = p.patterns_map[encoding];
                     if test_result {
                         if critical_index < usize::MAX {
@@ -168,7 +187,7 @@ pub fn get_inferior2<T: FnMut(usize, usize) -> ()>(board: &HexBoard, player: Pla
                             let dis = if d == 'v' { &DIS2_V } else if d == 'h' { &DIS2_H } else { &DIS2_DIAG };
                             let cx = x as i32 + dis[critical_index][0];
                             let cy = y as i32 + dis[critical_index][1];
-                            if cx >= 0 && cx < size && cy >= 0 && cy < size {
+                            if cx >= 0 && cx < height && cy >= 0 && cy < width {
                                 let (cx, cy) = (cx as usize, cy as usize);
 
                                 if !temp[cx][cy] {
@@ -206,8 +225,8 @@ pub fn get_inferior2<T: FnMut(usize, usize) -> ()>(board: &HexBoard, player: Pla
         }
     }
 
-    for x in 0..board.size {
-        for y in 0..board.size {
+    for x in 0..board.height {
+        for y in 0..board.width {
             if temp[x][y] {
                 action(x, y);
             }
@@ -224,7 +243,7 @@ pub fn fill_dead_and_captured(board: &HexBoard, next_player: Player) -> HexBoard
     while flag {
         flag = false;
 
-        let mut out = [(0, 0); MAX_SIZE * MAX_SIZE];
+        let mut out = vec![(0, 0); result.cell_count()];
         let mut out_count = 0;
         flag = flag || get_inferior1(&result, InferiorCellType::Dead1, |x, y| {
             out[out_count] = (x, y);
@@ -235,7 +254,7 @@ pub fn fill_dead_and_captured(board: &HexBoard, next_player: Player) -> HexBoard
             result.set(x, y, CellState::Red);
         }
 
-        let mut out = [(0, 0); MAX_SIZE * MAX_SIZE];
+        let mut out = vec![(0, 0); result.cell_count()];
         let mut out_count = 0;
         flag = flag || get_inferior2(&result, next_player, InferiorCellType::Captured2, |x, y| {
             out[out_count] = (x, y);
@@ -9,3 +9,9 @@ pub mod utils;
 pub mod two_distance;
 pub mod pattern;
 pub mod prior;
+pub mod opening;
+pub mod protocol;
+pub mod vc;
+pub mod game;
+pub mod sgf;
+pub mod visualize;
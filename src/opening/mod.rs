@@ -0,0 +1 @@
+pub mod swap;
\ No newline at end of file
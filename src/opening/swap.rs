@@ -0,0 +1,149 @@
+use crate::common::board::HexBoard;
+use crate::common::moves::Move;
+use crate::common::player::Player;
+
+/// how strong a first move is for the first player, from 0 to `size`.
+///
+/// Solved small boards and engine play agree that nearly every interior opening wins for the first
+/// player, while edge openings lose except at the two obtuse corners. Interior cells and the obtuse
+/// corners get `size`, edge cells get weaker the farther they are from the nearest obtuse corner,
+/// so the edge cells next to an obtuse corner are the closest to balanced
+pub fn opening_strength(size: usize, x: usize, y: usize) -> usize {
+    let interior = x > 0 && x < size - 1 && y > 0 && y < size - 1;
+    if interior {
+        return size;
+    }
+
+    let distance = |cx: usize, cy: usize| {
+        let dx = x as i32 - cx as i32;
+        let dy = y as i32 - cy as i32;
+        ((dx.abs() + dy.abs() + (dx + dy).abs()) / 2) as usize
+    };
+    size - distance(0, size - 1).min(distance(size - 1, 0))
+}
+
+/// opening strength table used under the swap rule, true when a first move is strong enough
+/// for the second player to swap: the interior cells and the obtuse corners
+pub fn swap_table(size: usize) -> Vec<bool> {
+    let mut result = vec![false; size * size];
+
+    for x in 0..size {
+        for y in 0..size {
+            result[x * size + y] = opening_strength(size, x, y) == size;
+        }
+    }
+
+    result
+}
+
+pub fn is_swappable(size: usize, x: usize, y: usize) -> bool {
+    swap_table(size)[x * size + y]
+}
+
+/// first move for red under the swap rule: the strongest cell that is not swappable,
+/// i.e. the one closest to balanced
+pub fn choose_opening(size: usize) -> (usize, usize) {
+    let table = swap_table(size);
+
+    let mut best = (0, 0);
+    let mut best_strength = 0;
+    // fall back to the whole board when every opening is swappable
+    let all_swappable = table.iter().all(|&x| x);
+
+    for x in 0..size {
+        for y in 0..size {
+            if table[x * size + y] && !all_swappable {
+                continue;
+            }
+
+            let strength = opening_strength(size, x, y);
+            if strength > best_strength {
+                best_strength = strength;
+                best = (x, y);
+            }
+        }
+    }
+
+    best
+}
+
+/// decide the move for `next_player` when the swap rule is in effect, or None when the
+/// position is past the opening and the regular search should decide.
+/// Rectangular boards are already decided by their shape, so they have no opening move
+pub fn opening_move(board: &HexBoard, next_player: Player) -> Option<Move> {
+    if !board.is_square() {
+        return None;
+    }
+    let size = board.width;
+
+    if board.empty_count == size * size && next_player == Player::Red {
+        let (x, y) = choose_opening(size);
+        return Some(Move::place(x, y));
+    }
+
+    if board.can_swap(next_player) {
+        let pos = (0..size * size).find(|&i| !board.is_empty(i / size, i % size)).unwrap();
+        if is_swappable(size, pos / size, pos % size) {
+            return Some(Move::Swap);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::HexBoard;
+    use crate::common::moves::Move;
+    use crate::common::player::Player;
+    use crate::opening::swap::{choose_opening, is_swappable, opening_move, opening_strength};
+
+    #[test]
+    fn test_swap_table() {
+        assert!(is_swappable(11, 5, 5));
+        assert!(is_swappable(11, 0, 10));
+        assert!(is_swappable(11, 10, 0));
+        assert!(!is_swappable(11, 0, 0));
+        assert!(!is_swappable(11, 0, 5));
+        assert!(!is_swappable(2, 0, 0));
+        assert!(is_swappable(2, 0, 1));
+    }
+
+    #[test]
+    fn test_choose_opening() {
+        for size in 1..=19 {
+            let (x, y) = choose_opening(size);
+            assert!(x < size && y < size);
+            assert!(size == 1 || !is_swappable(size, x, y));
+
+            // no other opening is both kept and stronger, so the choice is not in the losing set
+            let strength = opening_strength(size, x, y);
+            for i in 0..size * size {
+                let (cx, cy) = (i / size, i % size);
+                assert!(is_swappable(size, cx, cy) || opening_strength(size, cx, cy) <= strength);
+            }
+        }
+
+        // next to an obtuse corner rather than in the middle of an edge
+        assert_eq!(choose_opening(11), (0, 9));
+        assert!(opening_strength(11, 0, 5) < opening_strength(11, 0, 9));
+        assert!(opening_strength(11, 0, 0) < opening_strength(11, 0, 5));
+    }
+
+    #[test]
+    fn test_opening_move() {
+        let mut board = HexBoard::new(11);
+        let opening = opening_move(&board, Player::Red).unwrap();
+        board.play(opening, Player::Red);
+        assert_eq!(opening_move(&board, Player::Blue), None);
+
+        let mut board = HexBoard::new(11);
+        board.play(Move::place(5, 5), Player::Red);
+        assert_eq!(opening_move(&board, Player::Blue), Some(Move::Swap));
+
+        board.play(Move::Swap, Player::Blue);
+        assert_eq!(opening_move(&board, Player::Red), None);
+
+        assert_eq!(opening_move(&HexBoard::new_rect(7, 6), Player::Red), None);
+    }
+}
@@ -3,11 +3,12 @@ use itertools::Itertools;
 use smallvec::SmallVec;
 use rand::Rng;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use crate::common::board::{BoardPosition, CellState, HexBoard};
-use crate::common::constants::{DIS_PATTERN_12, DIS_PATTERN_6, MAX_SIZE};
+use crate::common::constants::DIS_PATTERN_12;
 use crate::common::player::Player;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatternItem {
     pub pattern_type: usize,
     pub gamma: f64,
@@ -276,21 +277,22 @@ fn get_pattern_move(x: i32, y: i32, index: usize) -> (i32, i32) {
 }
 
 impl HexBoard {
-    pub fn pattern_score(&self, out: &mut[f64], last_move: Option<(usize, usize)>, next_player: Player) -> [bool; MAX_SIZE * MAX_SIZE] {
-        let size = self.size;
+    pub fn pattern_score(&self, out: &mut[f64], last_move: Option<(usize, usize)>, next_player: Player) -> Vec<bool> {
+        let (width, height) = (self.width, self.height);
+        let count = self.cell_count();
 
         let mut total_score = 0.0;
-        let mut temp = [0.0; MAX_SIZE * MAX_SIZE];
+        let mut temp = vec![0.0; count];
 
-        let mut safe = [false; MAX_SIZE * MAX_SIZE];
-        let mut pruned = [false; MAX_SIZE * MAX_SIZE];
-        let mut consider = [false; MAX_SIZE * MAX_SIZE];
+        let mut safe = vec![false; count];
+        let mut pruned = vec![false; count];
+        let mut consider = vec![false; count];
 
-        for x in 0..size {
-            for y in 0..size {
+        for x in 0..height {
+            for y in 0..width {
                 if self.is_empty(x, y) {
                     let (key6, key12) = GLOBAL_PATTERNS.zobrist_keys.get_pattern_key_from_board(&self, x, y);
-                    let pos = x * size + y;
+                    let pos = x * width + y;
 
                     let pattern = get_pattern_both(&GLOBAL_PATTERNS, key6, key12, next_player);
                     if let Some(p) = pattern {
@@ -298,9 +300,9 @@ impl HexBoard {
                             pruned[pos] = true;
                         } else if p.pattern_type == 3 {
                             let (nx, ny) = get_pattern_move(x as i32, y as i32, p.killer);
-                            if nx >= 0 && nx < size as i32 && ny >= 0 && ny < size as i32 {
+                            if nx >= 0 && nx < height as i32 && ny >= 0 && ny < width as i32 {
                                 let (nx, ny) = (nx as usize, ny as usize);
-                                let npos = nx * size + ny;
+                                let npos = nx * width + ny;
 
                                 if !pruned[npos] {
                                     safe[npos] = true;
@@ -326,9 +328,9 @@ impl HexBoard {
         if let Some((x, y)) = last_move {
             for i in 0..12 {
                 let (nx, ny) = (x as i32 + DIS_PATTERN_12[i][0], y as i32 + DIS_PATTERN_12[i][1]);
-                if nx >= 0 && nx < size as i32 && ny >= 0 && ny < size as i32 {
+                if nx >= 0 && nx < height as i32 && ny >= 0 && ny < width as i32 {
                     let (nx, ny) = (nx as usize, ny as usize);
-                    let pos = nx * size + ny;
+                    let pos = nx * width + ny;
                     if self.is_empty(nx, ny) && consider[pos] {
                         let (key6, key12) = LOCAL_PATTERNS.zobrist_keys.get_pattern_key_from_board(&self, nx, ny);
 
@@ -342,7 +344,7 @@ impl HexBoard {
             }
         }
 
-        for i in 0..size * size {
+        for i in 0..count {
             out[i] = temp[i] / total_score;
         }
 
@@ -1,6 +1,6 @@
 use crate::common::board::HexBoard;
 use crate::common::player::Player;
 
-pub trait Prior {
+pub trait Prior: Send + Sync {
     fn prior(&self, board: &HexBoard, last_move: Option<(usize, usize)>, next_player: Player, out: &mut [f64]);
 }
\ No newline at end of file
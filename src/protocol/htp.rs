@@ -0,0 +1,287 @@
+use std::io::{BufRead, Write};
+use crate::common::constants::MAX_SIZE;
+use crate::common::moves::Move;
+use crate::common::player::Player;
+use crate::game::game::Game;
+use crate::solver::solver::Solver;
+
+const COMMANDS: [&str; 11] = [
+    "boardsize",
+    "clear_board",
+    "genmove",
+    "list_commands",
+    "name",
+    "param_game",
+    "play",
+    "quit",
+    "showboard",
+    "undo",
+    "version",
+];
+
+pub type HtpResult = Result<String, String>;
+
+pub fn parse_color(s: &str) -> Result<Player, String> {
+    match s.to_lowercase().as_str() {
+        "b" | "black" => Ok(Player::Red),
+        "w" | "white" => Ok(Player::Blue),
+        _ => Err(format!("invalid color: {}", s))
+    }
+}
+
+/// Hex Text Protocol, the GTP dialect spoken by HexGui and MoHex.
+///
+/// Black is the first player and connects top and bottom, so it is mapped to `Player::Red`.
+/// Cells are written as a column letter followed by a 1-based row number, e.g. `a1` is `(0, 0)`.
+/// `boardsize` takes the width and optionally the height of a rectangular board.
+/// The swap rule is off by default and is switched with `param_game allow_swap 1`, like in MoHex.
+pub struct HtpEngine {
+    pub game: Game,
+    pub solver: Box<dyn Solver>,
+    pub quit: bool,
+}
+
+impl HtpEngine {
+    pub fn new(size: usize, solver: Box<dyn Solver>) -> HtpEngine {
+        HtpEngine {
+            game: Game::new(size),
+            solver,
+            quit: false,
+        }
+    }
+
+    /// a new game on a board of the given shape, keeping the swap rule
+    fn new_game(&mut self, width: usize, height: usize) {
+        let allow_swap = self.game.allow_swap;
+        self.game = Game::new_rect(width, height);
+        self.game.allow_swap = allow_swap;
+    }
+
+    fn boardsize(&mut self, args: &[&str]) -> HtpResult {
+        if args.is_empty() || args.len() > 2 {
+            return Err(String::from("expected board size"));
+        }
+        let sizes = args.iter()
+            .map(|x| x.parse::<usize>().map_err(|_| format!("invalid board size: {}", x)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (width, height) = (sizes[0], *sizes.last().unwrap());
+        if sizes.iter().any(|&x| x == 0 || x > MAX_SIZE) {
+            return Err(format!("board size must be between 1 and {}", MAX_SIZE));
+        }
+
+        self.new_game(width, height);
+        Ok(String::new())
+    }
+
+    fn clear_board(&mut self) -> HtpResult {
+        self.game.clear();
+        Ok(String::new())
+    }
+
+    fn play(&mut self, args: &[&str]) -> HtpResult {
+        if args.len() != 2 {
+            return Err(String::from("expected color and move"));
+        }
+        let player = parse_color(args[0])?;
+        let mov = Move::parse(args[1], self.game.board.width, self.game.board.height)?;
+
+        if let Move::Place(cell) = mov {
+            if !self.game.board.is_empty(cell.x, cell.y) {
+                return Err(format!("cell is occupied: {}", cell));
+            }
+        }
+        self.game.play_as(player, mov)?;
+        Ok(String::new())
+    }
+
+    fn genmove(&mut self, args: &[&str]) -> HtpResult {
+        if args.len() != 1 {
+            return Err(String::from("expected color"));
+        }
+        let player = parse_color(args[0])?;
+
+        if self.game.is_over() {
+            return Err(String::from("game is over"));
+        }
+
+        let game = &self.game;
+        let mov = if game.allow_swap && (game.ply() == 0 || game.can_swap(player)) {
+            self.solver.solve_with_swap(&game.board, player)
+        } else {
+            Move::Place(self.solver.solve(&game.board, player).best_move)
+        };
+        if let Move::Place(cell) = mov {
+            if !self.game.board.is_empty(cell.x, cell.y) {
+                return Err(format!("solver returned an occupied cell: {}", cell));
+            }
+        }
+
+        self.game.play_as(player, mov)?;
+        Ok(mov.to_string())
+    }
+
+    fn undo(&mut self) -> HtpResult {
+        match self.game.undo() {
+            Some(_) => Ok(String::new()),
+            None => Err(String::from("no move to undo"))
+        }
+    }
+
+    fn param_game(&mut self, args: &[&str]) -> HtpResult {
+        match args {
+            [] => Ok(format!("[bool] allow_swap {}", self.game.allow_swap as usize)),
+            ["allow_swap", value] => {
+                self.game.allow_swap = match *value {
+                    "1" | "true" => true,
+                    "0" | "false" => false,
+                    _ => return Err(format!("invalid value: {}", value))
+                };
+                Ok(String::new())
+            },
+            _ => Err(String::from("unknown parameter"))
+        }
+    }
+
+    fn showboard(&self) -> HtpResult {
+        Ok(format!("\n{}", self.game.board.to_labeled_string()))
+    }
+
+    /// execute one command line, return None for empty lines and comments
+    pub fn execute(&mut self, line: &str) -> Option<String> {
+        let line = match line.find('#') {
+            Some(index) => &line[..index],
+            None => line
+        };
+        let mut tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.is_empty() {
+            return None;
+        }
+
+        let id = if tokens[0].parse::<usize>().is_ok() {
+            tokens.remove(0)
+        } else {
+            ""
+        };
+        if tokens.is_empty() {
+            return Some(format!("?{} missing command\n\n", id));
+        }
+
+        let args = &tokens[1..];
+        let result = match tokens[0] {
+            "boardsize" => self.boardsize(args),
+            "clear_board" => self.clear_board(),
+            "genmove" => self.genmove(args),
+            "list_commands" => Ok(COMMANDS.join("\n")),
+            "name" => Ok(String::from("hexx")),
+            "param_game" => self.param_game(args),
+            "play" => self.play(args),
+            "quit" => {
+                self.quit = true;
+                Ok(String::new())
+            },
+            "showboard" => self.showboard(),
+            "undo" => self.undo(),
+            "version" => Ok(String::from(env!("CARGO_PKG_VERSION"))),
+            command => Err(format!("unknown command: {}", command))
+        };
+
+        Some(match result {
+            Ok(x) => format!("={} {}\n\n", id, x),
+            Err(x) => format!("?{} {}\n\n", id, x),
+        })
+    }
+
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if let Some(response) = self.execute(&line) {
+                output.write_all(response.as_bytes())?;
+                output.flush()?;
+            }
+            if self.quit {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::cell::Cell;
+    use crate::common::player::Player;
+    use crate::protocol::htp::HtpEngine;
+    use crate::solver::report::SearchReport;
+    use crate::solver::solver::Solver;
+
+    struct FirstEmptySolver;
+
+    impl Solver for FirstEmptySolver {
+        fn solve(&mut self, board: &HexBoard, _next_player: Player) -> SearchReport {
+            for i in 0..board.cell_count() {
+                if board.get_abs(i) == CellState::Empty {
+                    return SearchReport {
+                        best_move: Cell::from_pos(i, board.width),
+                        ..Default::default()
+                    };
+                }
+            }
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_play_genmove_undo() {
+        let mut engine = HtpEngine::new(11, Box::new(FirstEmptySolver));
+
+        assert_eq!(engine.execute("boardsize 3").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("play b a1").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("play w a1").unwrap(), "? cell is occupied: a1\n\n");
+        assert_eq!(engine.execute("7 genmove w").unwrap(), "=7 b1\n\n");
+        assert_eq!(engine.game.board.get(0, 1), CellState::Blue);
+        assert_eq!(engine.execute("showboard").unwrap(), format!("= \n{}\n\n", engine.game.board.to_labeled_string()));
+        assert!(engine.game.board.to_labeled_string().contains(" 1 r b _ 1"));
+
+        assert_eq!(engine.execute("undo").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("undo").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("undo").unwrap(), "? no move to undo\n\n");
+        assert_eq!(engine.game.board.empty_count, 9);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut engine = HtpEngine::new(11, Box::new(FirstEmptySolver));
+
+        assert_eq!(engine.execute("play b c2").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("play w swap-pieces").unwrap(), "? swap is not allowed\n\n");
+        assert_eq!(engine.execute("param_game allow_swap 1").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("play w swap-pieces").unwrap(), "= \n\n");
+        assert_eq!(engine.game.board.get(2, 1), CellState::Blue);
+        assert_eq!(engine.execute("undo").unwrap(), "= \n\n");
+        assert_eq!(engine.game.board.get(1, 2), CellState::Red);
+        assert_eq!(engine.execute("genmove w").unwrap(), "= swap-pieces\n\n");
+        assert_eq!(engine.execute("undo").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("undo").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("genmove b").unwrap(), "= j1\n\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut engine = HtpEngine::new(11, Box::new(FirstEmptySolver));
+
+        assert!(engine.execute("").is_none());
+        assert!(engine.execute("# comment").is_none());
+        assert_eq!(engine.execute("foo").unwrap(), "? unknown command: foo\n\n");
+        assert_eq!(engine.execute("boardsize 26").unwrap(), "? board size must be between 1 and 25\n\n");
+        assert_eq!(engine.execute("boardsize 5 0").unwrap(), "? board size must be between 1 and 25\n\n");
+        assert_eq!(engine.execute("boardsize 5 6").unwrap(), "= \n\n");
+        assert_eq!((engine.game.board.width, engine.game.board.height), (5, 6));
+        assert_eq!(engine.execute("play b e6").unwrap(), "= \n\n");
+        assert_eq!(engine.execute("play w f1").unwrap(), "? coordinate out of board: f1\n\n");
+        assert_eq!(engine.execute("play red a1").unwrap(), "? invalid color: red\n\n");
+        assert_eq!(engine.execute("quit").unwrap(), "= \n\n");
+        assert!(engine.quit);
+    }
+}
@@ -0,0 +1,574 @@
+use std::fmt::{Display, Formatter};
+use crate::common::board::{CellState, HexBoard};
+use crate::common::cell::Cell;
+use crate::common::constants::MAX_SIZE;
+use crate::common::moves::Move;
+use crate::common::player::Player;
+use crate::game::game::Game;
+
+/// A node of an SGF game tree: its properties in file order and the nodes following it.
+/// The first child continues the main line, the other children are variations
+#[derive(Clone, Default, Debug, Eq, PartialEq)]
+pub struct SgfNode {
+    pub properties: Vec<(String, Vec<String>)>,
+    pub children: Vec<SgfNode>,
+}
+
+impl SgfNode {
+    pub fn new() -> SgfNode {
+        Default::default()
+    }
+
+    /// the first value of the property
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.get_all(id).first().map(|x| x.as_str())
+    }
+
+    pub fn get_all(&self, id: &str) -> &[String] {
+        self.properties.iter()
+            .find(|x| x.0 == id)
+            .map(|x| &x.1[..])
+            .unwrap_or(&[])
+    }
+
+    /// replace the values of the property with `value`
+    pub fn set(&mut self, id: &str, value: &str) {
+        match self.properties.iter_mut().find(|x| x.0 == id) {
+            Some(x) => x.1 = vec![String::from(value)],
+            None => self.properties.push((String::from(id), vec![String::from(value)])),
+        }
+    }
+
+    /// add a value to the property
+    pub fn add(&mut self, id: &str, value: &str) {
+        match self.properties.iter_mut().find(|x| x.0 == id) {
+            Some(x) => x.1.push(String::from(value)),
+            None => self.properties.push((String::from(id), vec![String::from(value)])),
+        }
+    }
+
+    /// the nodes of the main line, starting with this node
+    pub fn main_line(&self) -> Vec<&SgfNode> {
+        let mut result = vec![self];
+        let mut node = self;
+        while let Some(child) = node.children.first() {
+            result.push(child);
+            node = child;
+        }
+        result
+    }
+
+    /// the move of the node, None for a node without a move
+    pub fn get_move(&self, width: usize, height: usize) -> Result<Option<(Player, Move)>, String> {
+        for (id, player) in [("B", Player::Red), ("W", Player::Blue)] {
+            if let Some(value) = self.get(id) {
+                return Ok(Some((player, parse_sgf_move(value, width, height)?)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn write(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, ";")?;
+        for (id, values) in self.properties.iter() {
+            write!(f, "{}", id)?;
+            for value in values.iter() {
+                write!(f, "[{}]", escape(value))?;
+            }
+        }
+
+        if self.children.len() == 1 {
+            self.children[0].write(f)
+        } else {
+            for child in self.children.iter() {
+                write!(f, "(")?;
+                child.write(f)?;
+                write!(f, ")")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// writes the node as a game tree, `(;FF[4]...)`
+impl Display for SgfNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(")?;
+        self.write(f)?;
+        write!(f, ")")
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().is_some_and(|x| x.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(x) if x == c => Ok(()),
+            Some(x) => Err(format!("expected '{}', found '{}'", c, x)),
+            None => Err(format!("expected '{}', found the end", c)),
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.peek().cloned()
+    }
+
+    /// GameTree = "(" Sequence GameTree* ")"
+    fn game_tree(&mut self) -> Result<SgfNode, String> {
+        self.expect('(')?;
+
+        let mut sequence = Vec::new();
+        while self.peek() == Some(';') {
+            sequence.push(self.node()?);
+        }
+        if sequence.is_empty() {
+            return Err(String::from("game tree without nodes"));
+        }
+
+        let mut variations = Vec::new();
+        while self.peek() == Some('(') {
+            variations.push(self.game_tree()?);
+        }
+        self.expect(')')?;
+
+        let mut node = sequence.pop().unwrap();
+        node.children = variations;
+        while let Some(mut parent) = sequence.pop() {
+            parent.children = vec![node];
+            node = parent;
+        }
+        Ok(node)
+    }
+
+    /// Node = ";" Property*
+    fn node(&mut self) -> Result<SgfNode, String> {
+        self.expect(';')?;
+
+        let mut node = SgfNode::new();
+        while self.peek().is_some_and(|x| x.is_ascii_uppercase()) {
+            let mut id = String::new();
+            while let Some(c) = self.chars.peek().cloned().filter(|x| x.is_ascii_uppercase()) {
+                id.push(c);
+                self.chars.next();
+            }
+
+            let mut values = Vec::new();
+            while self.peek() == Some('[') {
+                values.push(self.value()?);
+            }
+            if values.is_empty() {
+                return Err(format!("property {} without value", id));
+            }
+            node.properties.push((id, values));
+        }
+
+        Ok(node)
+    }
+
+    /// properties without the leading ";", checked to be read to the end
+    fn node_without_semicolon(mut self) -> Result<SgfNode, String> {
+        let mut node = SgfNode::new();
+        while self.peek().is_some() {
+            let mut id = String::new();
+            while let Some(c) = self.chars.peek().cloned().filter(|x| x.is_ascii_uppercase()) {
+                id.push(c);
+                self.chars.next();
+            }
+            if id.is_empty() {
+                return Err(format!("expected a property, found '{}'", self.peek().unwrap()));
+            }
+            let value = self.value()?;
+            node.properties.push((id, vec![value]));
+        }
+        Ok(node)
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        self.expect('[')?;
+
+        let mut result = String::new();
+        loop {
+            match self.chars.next() {
+                Some('\\') => match self.chars.next() {
+                    Some(c) => result.push(c),
+                    None => break
+                },
+                Some(']') => return Ok(result),
+                Some(c) => result.push(c),
+                None => break
+            }
+        }
+        Err(String::from("unterminated property value"))
+    }
+}
+
+/// parse an SGF collection, one root node for every game tree
+pub fn parse(s: &str) -> Result<Vec<SgfNode>, String> {
+    let mut parser = Parser { chars: s.chars().peekable() };
+    let mut result = Vec::new();
+    while parser.peek().is_some() {
+        result.push(parser.game_tree()?);
+    }
+    Ok(result)
+}
+
+/// parse a move value: a cell like `c3` as written by HexGui, a cell like `cc` as written by
+/// Little Golem, `swap-pieces`, `swap` or `resign`
+pub fn parse_sgf_move(s: &str, width: usize, height: usize) -> Result<Move, String> {
+    let chars: Vec<char> = s.trim().to_lowercase().chars().collect();
+    if chars.len() == 2 && chars.iter().all(|x| x.is_ascii_lowercase()) {
+        let cell = Cell::new(chars[1] as usize - 'a' as usize, chars[0] as usize - 'a' as usize);
+        if !cell.is_in_board(width, height) {
+            return Err(format!("coordinate out of board: {}", s));
+        }
+        return Ok(Move::Place(cell));
+    }
+
+    Move::parse(s, width, height)
+}
+
+/// parse the moves of a game written as bare move properties, like a line of `data/data.txt`:
+/// `B[m12] W[e9] ...`
+pub fn parse_moves(s: &str, width: usize, height: usize) -> Result<Vec<(Player, Move)>, String> {
+    let node = Parser { chars: s.chars().peekable() }.node_without_semicolon()?;
+
+    let mut result = Vec::new();
+    for (id, values) in node.properties.iter() {
+        let player = match id.as_str() {
+            "B" => Player::Red,
+            "W" => Player::Blue,
+            _ => return Err(format!("not a move: {}", id))
+        };
+        for value in values.iter() {
+            result.push((player, parse_sgf_move(value, width, height)?));
+        }
+    }
+    Ok(result)
+}
+
+/// width and height of `SZ[11]` or `SZ[7:5]`
+fn parse_size(s: &str) -> Result<(usize, usize), String> {
+    let sizes = s.split(':')
+        .map(|x| x.trim().parse::<usize>().map_err(|_| format!("invalid board size: {}", s)))
+        .collect::<Result<Vec<_>, _>>()?;
+    match sizes[..] {
+        [size] => Ok((size, size)),
+        [width, height] => Ok((width, height)),
+        _ => Err(format!("invalid board size: {}", s))
+    }
+}
+
+/// setup stones are only read from the root
+fn check_setup(node: &SgfNode) -> Result<(), String> {
+    for id in ["AB", "AW", "AE"] {
+        if !node.get_all(id).is_empty() {
+            return Err(format!("setup stones outside the root: {}", id));
+        }
+    }
+    Ok(())
+}
+
+/// read a Hex game without variations like `read_main_line`.
+/// A tree with variations is an error rather than cut down to its main line
+pub fn read_game(root: &SgfNode) -> Result<Game, String> {
+    if root.main_line().iter().any(|x| x.children.len() > 1) {
+        return Err(String::from("the game has variations, read them with read_variations"));
+    }
+    read_main_line(root)
+}
+
+/// read the main line of a Hex game. Stones set up with `AB` and `AW` in the root make the
+/// initial position, `PL` gives the first player. Swap moves switch the swap rule on.
+/// `PB` and `PW` name the players and `C` comments the position after the move of its node
+pub fn read_main_line(root: &SgfNode) -> Result<Game, String> {
+
+    if let Some(gm) = root.get("GM") {
+        if gm.trim() != "11" {
+            return Err(format!("not a Hex game: GM[{}]", gm));
+        }
+    }
+    let (width, height) = parse_size(root.get("SZ").unwrap_or("11"))?;
+    if width == 0 || height == 0 || width > MAX_SIZE || height > MAX_SIZE {
+        return Err(format!("unsupported board size: {}x{}", width, height));
+    }
+
+    let mut board = HexBoard::new_rect(width, height);
+    for (id, state) in [("AB", CellState::Red), ("AW", CellState::Blue)] {
+        for value in root.get_all(id) {
+            match parse_sgf_move(value, width, height)? {
+                Move::Place(cell) => board.set(cell.x, cell.y, state),
+                _ => return Err(format!("invalid setup stone: {}", value)),
+            }
+        }
+    }
+    let first_player = match root.get("PL") {
+        Some("W") => Player::Blue,
+        _ => Player::Red
+    };
+
+    let mut game = Game::from_position(board, first_player);
+    game.red_name = root.get("PB").map(String::from);
+    game.blue_name = root.get("PW").map(String::from);
+    for (i, node) in root.main_line().into_iter().enumerate() {
+        if i > 0 {
+            check_setup(node)?;
+        }
+        if let Some((player, mov)) = node.get_move(width, height)? {
+            if mov == Move::Swap {
+                game.allow_swap = true;
+            }
+            game.play_as(player, mov)?;
+        }
+        if let Some(comment) = node.get("C") {
+            let comment = match game.comment_at(game.ply()) {
+                Some(x) => format!("{}\n{}", x, comment),
+                None => String::from(comment),
+            };
+            game.set_comment(&comment);
+        }
+    }
+
+    Ok(game)
+}
+
+/// every line of moves from the root to a leaf, the main line first
+pub fn read_variations(root: &SgfNode, width: usize, height: usize) -> Result<Vec<Vec<(Player, Move)>>, String> {
+    let mut result = Vec::new();
+    let mut stack = vec![(root, Vec::new())];
+    while let Some((node, mut line)) = stack.pop() {
+        if !std::ptr::eq(node, root) {
+            check_setup(node)?;
+        }
+        if let Some(mov) = node.get_move(width, height)? {
+            line.push(mov);
+        }
+        if node.children.is_empty() {
+            result.push(line);
+        } else {
+            for child in node.children.iter().rev() {
+                stack.push((child, line.clone()));
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn move_node(player: Player, mov: Move) -> SgfNode {
+    let mut node = SgfNode::new();
+    node.set(if player == Player::Red { "B" } else { "W" }, &mov.to_string());
+    node
+}
+
+/// the game as an SGF tree: the root with the game information and one node per move,
+/// with the names of the players, the result and the comments of the positions.
+/// Properties that `read_game` does not know are not kept
+pub fn write_game(game: &Game) -> SgfNode {
+    let initial = game.initial_board();
+    let (width, height) = (initial.width, initial.height);
+
+    let mut root = SgfNode::new();
+    root.set("FF", "4");
+    root.set("GM", "11");
+    root.set("AP", "hexx");
+    if width == height {
+        root.set("SZ", &width.to_string());
+    } else {
+        root.set("SZ", &format!("{}:{}", width, height));
+    }
+    for pos in 0..initial.cell_count() {
+        let cell = Cell::from_pos(pos, width).to_string();
+        match initial.get_abs(pos) {
+            CellState::Red => root.add("AB", &cell),
+            CellState::Blue => root.add("AW", &cell),
+            CellState::Empty => {}
+        }
+    }
+    if initial.empty_count != initial.cell_count() {
+        root.set("PL", if game.first_player() == Player::Red { "B" } else { "W" });
+    }
+    if let Some(name) = &game.red_name {
+        root.set("PB", name);
+    }
+    if let Some(name) = &game.blue_name {
+        root.set("PW", name);
+    }
+    let resigned = matches!(game.history().last(), Some((_, Move::Resign)));
+    match game.winner() {
+        Some(Player::Red) => root.set("RE", if resigned { "B+Resign" } else { "B+" }),
+        Some(Player::Blue) => root.set("RE", if resigned { "W+Resign" } else { "W+" }),
+        None => {}
+    }
+
+    if let Some(comment) = game.comment_at(0) {
+        root.set("C", comment);
+    }
+
+    let nodes: Vec<SgfNode> = game.history().iter().enumerate().map(|(i, &(player, mov))| {
+        let mut node = move_node(player, mov);
+        if let Some(comment) = game.comment_at(i + 1) {
+            node.set("C", comment);
+        }
+        node
+    }).collect();
+    let mut result = root;
+    let mut tail = &mut result;
+    for node in nodes {
+        tail.children.push(node);
+        tail = &mut tail.children[0];
+    }
+
+    result
+}
+
+/// `write_game` with the lines of moves from the initial position, as read by `read_variations`,
+/// added as variations where they leave the moves of the game
+pub fn write_variations(game: &Game, lines: &[Vec<(Player, Move)>]) -> SgfNode {
+    let (width, height) = (game.initial_board().width, game.initial_board().height);
+
+    let mut root = write_game(game);
+    for line in lines.iter() {
+        let mut node = &mut root;
+        for &(player, mov) in line.iter() {
+            let index = match node.children.iter().position(|x| x.get_move(width, height) == Ok(Some((player, mov)))) {
+                Some(x) => x,
+                None => {
+                    node.children.push(move_node(player, mov));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[index];
+        }
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::CellState;
+    use crate::common::moves::Move;
+    use crate::common::player::Player;
+    use crate::game::game::Game;
+    use crate::sgf::sgf::{parse, parse_moves, read_game, read_main_line, read_variations, write_game, write_variations};
+
+    #[test]
+    fn test_parse() {
+        let s = "(;FF[4]GM[11]SZ[5]PB[alice]C[a \\] bracket]\n;B[c3];W[swap-pieces](;B[a1]C[main])(;B[e5]))";
+        let roots = parse(s).unwrap();
+        assert_eq!(roots.len(), 1);
+
+        let root = &roots[0];
+        assert_eq!(root.get("PB"), Some("alice"));
+        assert_eq!(root.get("C"), Some("a ] bracket"));
+        assert_eq!(root.main_line().len(), 4);
+
+        let lines = read_variations(root, 5, 5).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][2], (Player::Red, Move::place(0, 0)));
+        assert_eq!(lines[1][2], (Player::Red, Move::place(4, 4)));
+
+        // writing gives back the same tree
+        assert_eq!(parse(&root.to_string()).unwrap()[0], *root);
+
+        // and the same lines through a game
+        let game = read_main_line(root).unwrap();
+        let written = write_variations(&game, &lines);
+        assert_eq!(read_variations(&written, 5, 5).unwrap(), lines);
+        assert_eq!(written.get("PB"), Some("alice"));
+        assert_eq!(written.main_line()[3].get("C"), Some("main"));
+
+        assert!(parse("(;B[a1]").is_err());
+        assert!(parse("(;B)").is_err());
+    }
+
+    #[test]
+    fn test_game() {
+        let root = &parse("(;FF[4]GM[11]SZ[5];B[c3];W[swap-pieces];B[b2])").unwrap()[0];
+        let game = read_game(root).unwrap();
+        assert_eq!(game.ply(), 3);
+        assert!(game.allow_swap);
+        assert_eq!(game.board.get(2, 2), CellState::Blue);
+        assert_eq!(game.board.get(1, 1), CellState::Red);
+
+        let written = write_game(&game);
+        assert_eq!(written.to_string(), "(;FF[4]GM[11]AP[hexx]SZ[5];B[c3];W[swap-pieces];B[b2])");
+
+        // little golem coordinates and a rectangular board
+        let root = &parse("(;GM[11]SZ[4:3];W[ab];B[resign])").unwrap()[0];
+        let game = read_game(root).unwrap();
+        assert_eq!((game.board.width, game.board.height), (4, 3));
+        assert_eq!(game.board.get(1, 0), CellState::Blue);
+        assert_eq!(game.ply(), 2);
+        assert_eq!(game.winner(), Some(Player::Blue));
+        assert_eq!(write_game(&game).get("RE"), Some("W+Resign"));
+
+        assert!(read_game(&parse("(;GM[1]SZ[19])").unwrap()[0]).is_err());
+
+        // variations are not cut off silently
+        let root = &parse("(;GM[11]SZ[5];B[c3](;W[a1])(;W[e5]))").unwrap()[0];
+        assert!(read_game(root).is_err());
+        assert_eq!(read_variations(root, 5, 5).unwrap().len(), 2);
+
+        // players and comments
+        let s = "(;FF[4]GM[11]AP[hexx]SZ[5]PB[alice]PW[bob]C[start];B[c3]C[center];W[swap-pieces])";
+        let game = read_game(&parse(s).unwrap()[0]).unwrap();
+        assert_eq!(game.red_name.as_deref(), Some("alice"));
+        assert_eq!(game.blue_name.as_deref(), Some("bob"));
+        assert_eq!(game.comment_at(0), Some("start"));
+        assert_eq!(game.comment_at(1), Some("center"));
+        assert_eq!(write_game(&game).to_string(), s);
+    }
+
+    #[test]
+    fn test_result_and_setup() {
+        let mut game = Game::new(2);
+        game.play(Move::place(0, 0)).unwrap();
+        game.play(Move::place(0, 1)).unwrap();
+        game.play(Move::place(1, 0)).unwrap();
+        let root = write_game(&game);
+        assert_eq!(root.get("RE"), Some("B+"));
+
+        let root = &parse("(;GM[11]SZ[3]AB[a1][b2]AW[c1]PL[W];W[a2])").unwrap()[0];
+        let game = read_game(root).unwrap();
+        assert_eq!(game.first_player(), Player::Blue);
+        assert_eq!(game.initial_board().empty_count, 6);
+        assert_eq!(game.board.get(1, 0), CellState::Blue);
+
+        let written = write_game(&game);
+        assert_eq!(written.get_all("AB").len(), 2);
+        assert_eq!(written.get("PL"), Some("W"));
+        assert!(read_game(&written).unwrap().board == game.board);
+
+        // setup stones are only read from the root
+        let root = &parse("(;GM[11]SZ[3];B[a1];AB[b2])").unwrap()[0];
+        assert!(read_game(root).is_err());
+        assert!(read_variations(root, 3, 3).is_err());
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        let moves = parse_moves("B[m12] W[e9] B[swap]\n", 13, 13).unwrap();
+        assert_eq!(moves, vec![
+            (Player::Red, Move::place(11, 12)),
+            (Player::Blue, Move::place(8, 4)),
+            (Player::Red, Move::Swap),
+        ]);
+        assert!(parse_moves("B[m12] X[e9]", 13, 13).is_err());
+        assert!(parse_moves("B[n1]", 13, 13).is_err());
+    }
+}
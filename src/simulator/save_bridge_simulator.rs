@@ -1,4 +1,5 @@
 use crate::common::board::{CellState, HexBoard};
+use crate::common::constants::MAX_SIZE;
 use crate::common::player::Player;
 use crate::simulator::simulator::Simulator;
 use lazy_static::lazy_static;
@@ -11,7 +12,7 @@ pub struct SaveBridgeSimulator;
 lazy_static! {
     static ref MOVES: Vec<usize> = {
         let mut result = Vec::new();
-        for i in 0..19 * 19 {
+        for i in 0..MAX_SIZE * MAX_SIZE {
             result.push(i);
         }
         result
@@ -21,8 +22,8 @@ lazy_static! {
 fn save_bridge(board: &mut HexBoard, pos: usize, player: Player) -> bool {
     let dis = [[-1, 0], [-1, 1], [0, 1], [1, 0], [1, -1], [0, -1]];
 
-    let size = board.size as i32;
-    let (x, y) = (pos as i32 / size, pos as i32 % size);
+    let (width, height) = (board.width as i32, board.height as i32);
+    let (x, y) = (pos as i32 / width, pos as i32 % width);
 
     let mut temp: SmallVec<[usize; 3]> = SmallVec::new();
 
@@ -52,8 +53,8 @@ fn save_bridge(board: &mut HexBoard, pos: usize, player: Player) -> bool {
             let nx = x + dis[(i + 1) % 6][0];
             let ny = y + dis[(i + 1) % 6][1];
 
-            if nx >= 0 && nx < size && ny >= 0 && ny < size {
-                let npos = nx * size + ny;
+            if nx >= 0 && nx < height && ny >= 0 && ny < width {
+                let npos = nx * width + ny;
 
                 if board.get_abs(npos as usize) == CellState::Empty {
                     temp.push(npos as usize);
@@ -82,8 +83,12 @@ fn save_bridge(board: &mut HexBoard, pos: usize, player: Player) -> bool {
 
 impl Simulator for SaveBridgeSimulator {
     fn simulate_once(&self, board: &HexBoard, next_player: Player) -> Player {
+        self.playout(board, next_player).0
+    }
+
+    fn playout(&self, board: &HexBoard, next_player: Player) -> (Player, HexBoard) {
         let mut board = board.clone();
-        let ss = board.size * board.size;
+        let ss = board.cell_count();
 
         let mut moves = MOVES.clone();
         let mut moves = &mut moves[0..ss];
@@ -100,11 +105,34 @@ impl Simulator for SaveBridgeSimulator {
                 if !save_bridge(&mut board, position, next_player) {
                     next_player = next_player.reverse();
                 }
+                // a chain touches both edges, the rest of the board does not matter
+                if board.winner().is_some() {
+                    break;
+                }
             }
 
             moves.swap(valid_index, ss - i - 1);
         }
 
-        board.winner_definite()
+        (board.winner_definite(), board)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::HexBoard;
+    use crate::common::player::Player;
+    use crate::simulator::save_bridge_simulator::SaveBridgeSimulator;
+    use crate::simulator::simulator::Simulator;
+
+    #[test]
+    fn test_playout_stops_at_winner() {
+        let mut stopped_early = false;
+        for _ in 0..20 {
+            let (winner, board) = SaveBridgeSimulator.playout(&HexBoard::new(11), Player::Red);
+            assert_eq!(board.winner(), Some(winner));
+            stopped_early |= board.empty_count > 0;
+        }
+        assert!(stopped_early);
     }
 }
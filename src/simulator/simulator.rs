@@ -1,10 +1,16 @@
 use crate::common::board::HexBoard;
 use crate::common::player::Player;
 
-pub trait Simulator {
+pub trait Simulator: Send + Sync {
     /// simulate once and get the winner
     fn simulate_once(&self, board: &HexBoard, next_player: Player) -> Player;
 
+    /// simulate once and get the winner and the final board, whose cells tell which player filled them.
+    /// The default implementation does not report the cells and returns the board unchanged
+    fn playout(&self, board: &HexBoard, next_player: Player) -> (Player, HexBoard) {
+        (self.simulate_once(board, next_player), board.clone())
+    }
+
     /// return how many times RED wins
     fn simulate(&self, board: &HexBoard, next_player: Player, count: usize) -> usize {
         let mut result = 0;
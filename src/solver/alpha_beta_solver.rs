@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::common::board::{CellState, HexBoard};
+use crate::common::cell::Cell;
+use crate::common::player::Player;
+use crate::solver::report::{ChildReport, SearchReport};
+use crate::solver::solver::{fallback_move, Solver};
+
+const WIN: i32 = 1_000_000;
+/// scores above this are wins found by the search
+const WIN_BOUND: i32 = WIN - 1000;
+/// two distance of a player who cannot connect any more
+const MAX_POTENTIAL: usize = 1000;
+/// values of wins that are not proven are clamped to this for the win rate
+const MAX_HEURISTIC_VALUE: i32 = 1000;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlphaBetaSolverConfig {
+    /// depth of the last iteration of iterative deepening
+    pub max_depth: usize,
+    /// only search this many moves of each position, in the order of the pattern score
+    pub width: Option<usize>,
+    /// only search the cells `pattern_score` considers
+    pub pattern_pruning: bool,
+    pub time_limit: Option<Duration>,
+}
+
+impl AlphaBetaSolverConfig {
+    /// whether every move is searched, so that wins and losses found by the search are proofs
+    pub fn is_exhaustive(&self) -> bool {
+        self.width.is_none() && !self.pattern_pruning
+    }
+}
+
+impl Default for AlphaBetaSolverConfig {
+    fn default() -> Self {
+        AlphaBetaSolverConfig {
+            max_depth: 4,
+            width: Some(12),
+            pattern_pruning: true,
+            time_limit: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+struct Entry {
+    depth: usize,
+    value: i32,
+    bound: Bound,
+    best: Option<u16>,
+}
+
+/// two distance potential of `player`: the smallest sum of the two distances to both edges
+/// over the empty cells, and how many cells reach it
+pub fn potential(board: &HexBoard, player: Player) -> (usize, usize) {
+    let dis = board.two_distance_sum(player);
+    let mut result = (MAX_POTENTIAL, 0);
+    for (i, &d) in dis.iter().enumerate() {
+        if board.get_abs(i) != CellState::Empty {
+            continue;
+        }
+        let d = d.min(MAX_POTENTIAL);
+        if d < result.0 {
+            result = (d, 1);
+        } else if d == result.0 {
+            result.1 += 1;
+        }
+    }
+
+    result
+}
+
+/// Queenbee style evaluation for the player to move: the difference of the two distance potentials,
+/// ties are broken by the number of cells reaching the potential
+pub fn evaluate(board: &HexBoard, next_player: Player) -> i32 {
+    let (mine, my_count) = potential(board, next_player);
+    let (theirs, their_count) = potential(board, next_player.reverse());
+
+    (theirs as i32 - mine as i32) * 100 + my_count as i32 - their_count as i32
+}
+
+/// Iterative deepening alpha-beta with a transposition table.
+/// Moves are ordered by the transposition table move and then by `pattern_score`
+pub struct AlphaBetaSolver {
+    pub config: AlphaBetaSolverConfig,
+    tt: HashMap<u64, Entry>,
+    nodes: usize,
+    deadline: Option<Instant>,
+    aborted: bool,
+}
+
+impl AlphaBetaSolver {
+    pub fn new(config: AlphaBetaSolverConfig) -> AlphaBetaSolver {
+        AlphaBetaSolver {
+            config,
+            tt: HashMap::new(),
+            nodes: 0,
+            deadline: None,
+            aborted: false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.tt.clear();
+    }
+
+    fn out_of_budget(&mut self) -> bool {
+        if let Some(deadline) = self.deadline {
+            // checking the clock is slow compared to a node
+            if self.nodes.is_multiple_of(64) && Instant::now() >= deadline {
+                self.aborted = true;
+            }
+        }
+        self.aborted
+    }
+
+    fn ordered_moves(&self, board: &HexBoard, next_player: Player, first: Option<u16>) -> Vec<u16> {
+        let ss = board.cell_count();
+        let mut score = vec![0.0; ss];
+        let consider = board.pattern_score(&mut score[..], None, next_player);
+
+        let mut moves: Vec<usize> = (0..ss)
+            .filter(|&i| (consider[i] || !self.config.pattern_pruning) && board.get_abs(i) == CellState::Empty)
+            .collect();
+        if moves.is_empty() {
+            moves = (0..ss).filter(|&i| board.get_abs(i) == CellState::Empty).collect();
+        }
+        moves.sort_by(|&a, &b| score[b].total_cmp(&score[a]));
+        if let Some(width) = self.config.width {
+            moves.truncate(width);
+        }
+
+        let mut result: Vec<u16> = moves.into_iter().map(|x| x as u16).collect();
+        if let Some(first) = first {
+            result.retain(|&x| x != first);
+            result.insert(0, first);
+        }
+        result
+    }
+
+    fn negamax(&mut self, board: &HexBoard, next_player: Player, depth: usize, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+        self.nodes += 1;
+        if let Some(winner) = board.winner() {
+            return if winner == next_player { WIN - ply } else { ply - WIN };
+        }
+        if depth == 0 || self.out_of_budget() {
+            return evaluate(board, next_player);
+        }
+
+        let key = board.hash_with_player(next_player);
+        let mut first = None;
+        if let Some(entry) = self.tt.get(&key) {
+            first = entry.best;
+            if entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.value,
+                    Bound::Lower if entry.value >= beta => return entry.value,
+                    Bound::Upper if entry.value <= alpha => return entry.value,
+                    _ => {}
+                }
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best_value = -WIN - 1;
+        let mut best = None;
+        for mov in self.ordered_moves(board, next_player, first) {
+            let mut child = board.clone();
+            child.set_abs(mov as usize, next_player.to_cell());
+            let value = -self.negamax(&child, next_player.reverse(), depth - 1, -beta, -alpha, ply + 1);
+
+            if value > best_value {
+                best_value = value;
+                best = Some(mov);
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if !self.aborted {
+            let bound = if best_value <= original_alpha {
+                Bound::Upper
+            } else if best_value >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.tt.insert(key, Entry { depth, value: best_value, bound, best });
+        }
+
+        best_value
+    }
+
+    /// follow the best moves stored in the transposition table
+    fn principal_variation(&self, board: &HexBoard, next_player: Player, max_len: usize) -> Vec<Cell> {
+        let width = board.width;
+        let mut result = Vec::new();
+        let mut board = board.clone();
+        let mut player = next_player;
+        while result.len() < max_len {
+            let mov = match self.tt.get(&board.hash_with_player(player)).and_then(|x| x.best) {
+                Some(x) => x as usize,
+                None => break
+            };
+            result.push(Cell::from_pos(mov, width));
+            board.set_abs(mov, player.to_cell());
+            player = player.reverse();
+        }
+
+        result
+    }
+}
+
+impl Solver for AlphaBetaSolver {
+    fn solve(&mut self, board: &HexBoard, next_player: Player) -> SearchReport {
+        let start = Instant::now();
+        self.nodes = 0;
+        self.aborted = false;
+        self.deadline = self.config.time_limit.map(|x| start + x);
+
+        let mut value = 0;
+        let mut depth = 0;
+        for d in 1..=self.config.max_depth.min(board.empty_count).max(1) {
+            let v = self.negamax(board, next_player, d, -WIN - 1, WIN + 1, 0);
+            if self.aborted {
+                break;
+            }
+            value = v;
+            depth = d;
+            if v.abs() >= WIN_BOUND {
+                break;
+            }
+        }
+
+        let width = board.width;
+        let principal_variation = self.principal_variation(board, next_player, depth.max(1));
+        // with pruned moves a win may rest on a defence that was not searched
+        let proven = if !self.config.is_exhaustive() {
+            None
+        } else if value >= WIN_BOUND {
+            Some(next_player)
+        } else if value <= -WIN_BOUND {
+            Some(next_player.reverse())
+        } else {
+            None
+        };
+        let win_rate = match proven {
+            Some(x) if x == next_player => 1.0,
+            Some(_) => 0.0,
+            None => {
+                let value = value.clamp(-MAX_HEURISTIC_VALUE, MAX_HEURISTIC_VALUE);
+                1.0 / (1.0 + (-value as f64 / 200.0).exp())
+            },
+        };
+        let children = self.ordered_moves(board, next_player, None).into_iter().map(|mov| ChildReport {
+            mov: Cell::from_pos(mov as usize, width),
+            ..Default::default()
+        }).collect();
+
+        SearchReport {
+            best_move: principal_variation.first().cloned().unwrap_or_else(|| fallback_move(board, next_player)),
+            win_rate,
+            principal_variation,
+            children,
+            total_nodes: self.nodes,
+            elapsed: start.elapsed(),
+            proven,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::cell::Cell;
+    use crate::common::player::Player;
+    use crate::solver::alpha_beta_solver::{evaluate, potential, AlphaBetaSolver, AlphaBetaSolverConfig};
+    use crate::solver::solver::Solver;
+
+    #[test]
+    fn test_evaluate() {
+        let board = HexBoard::new(5);
+        assert_eq!(potential(&board, Player::Red), potential(&board, Player::Blue));
+        assert_eq!(evaluate(&board, Player::Red), 0);
+
+        let mut board = HexBoard::new(5);
+        board.set(2, 2, CellState::Red);
+        assert!(evaluate(&board, Player::Red) > 0);
+        assert!(evaluate(&board, Player::Blue) < 0);
+    }
+
+    #[test]
+    fn test_win_in_one() {
+        let mut board = HexBoard::new(3);
+        board.set(0, 0, CellState::Red);
+        board.set(1, 0, CellState::Red);
+        board.set(0, 1, CellState::Blue);
+        board.set(1, 1, CellState::Blue);
+
+        let mut solver = AlphaBetaSolver::new(Default::default());
+        let report = solver.solve(&board, Player::Red);
+        assert_eq!(report.best_move, Cell::new(2, 0));
+        // moves are pruned, so the win is not a proof
+        assert_eq!(report.proven, None);
+
+        let mut solver = AlphaBetaSolver::new(AlphaBetaSolverConfig {
+            width: None,
+            pattern_pruning: false,
+            ..Default::default()
+        });
+        let report = solver.solve(&board, Player::Red);
+        assert_eq!(report.best_move, Cell::new(2, 0));
+        assert_eq!(report.proven, Some(Player::Red));
+    }
+
+    #[test]
+    fn test_small_board() {
+        let config = AlphaBetaSolverConfig {
+            max_depth: 9,
+            width: None,
+            pattern_pruning: false,
+            ..Default::default()
+        };
+        let mut solver = AlphaBetaSolver::new(config);
+        let report = solver.solve(&HexBoard::new(3), Player::Red);
+        assert_eq!(report.proven, Some(Player::Red));
+
+        let Cell { x, y } = report.best_move;
+        let mut board = HexBoard::new(3);
+        board.set(x, y, CellState::Red);
+        let report = solver.solve(&board, Player::Blue);
+        assert_eq!(report.proven, Some(Player::Red));
+        assert_eq!(report.win_rate, 0.0);
+    }
+
+    #[test]
+    fn test_no_time() {
+        let mut solver = AlphaBetaSolver::new(AlphaBetaSolverConfig {
+            time_limit: Some(Duration::ZERO),
+            ..Default::default()
+        });
+        let mut board = HexBoard::new(5);
+        board.set(0, 0, CellState::Red);
+
+        let report = solver.solve(&board, Player::Blue);
+        assert!(board.is_empty(report.best_move.x, report.best_move.y));
+    }
+
+    #[test]
+    fn test_pruned_defence() {
+        // red wins at c2 unless blue takes it, and c2 is not among the 12 moves blue searches
+        let board: HexBoard = "2r2r2/6r1/2r5/2r1b3/2r5/2r5/2r5/2r5".parse().unwrap();
+        let defence = Cell::new(1, 2);
+
+        let mut solver = AlphaBetaSolver::new(AlphaBetaSolverConfig {
+            max_depth: 2,
+            ..Default::default()
+        });
+        let moves = solver.ordered_moves(&board, Player::Blue, None);
+        assert_eq!(moves.len(), 12);
+        assert!(!moves.contains(&(defence.pos(board.width) as u16)));
+
+        // the pruned search sees every move lose, but that is not a proof
+        let report = solver.solve(&board, Player::Blue);
+        assert_ne!(report.best_move, defence);
+        assert_eq!(report.proven, None);
+        assert!(report.win_rate > 0.0 && report.win_rate < 0.01);
+
+        let mut solver = AlphaBetaSolver::new(AlphaBetaSolverConfig {
+            max_depth: 2,
+            width: None,
+            pattern_pruning: false,
+            ..Default::default()
+        });
+        let report = solver.solve(&board, Player::Blue);
+        assert_eq!(report.best_move, defence);
+        assert_eq!(report.proven, None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use crate::common::board::{CellState, HexBoard};
+use crate::common::cell::Cell;
+use crate::common::player::Player;
+use crate::cutoff::cutoff::Cutoff;
+use crate::cutoff::mustplay_cutoff::MustplayCutoff;
+use crate::solver::report::{ChildReport, SearchReport};
+use crate::solver::solver::{fallback_move, Solver};
+
+const INF: u32 = u32::MAX / 2;
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DfpnSolverConfig {
+    /// give up after this many node visits
+    pub node_limit: Option<usize>,
+    /// give up after this much wall-clock time
+    pub time_limit: Option<Duration>,
+}
+
+/// proof and disproof numbers of a position, from the view of the player to move
+struct Entry {
+    phi: u32,
+    delta: u32,
+    moves: Vec<u16>,
+}
+
+/// Depth-first proof-number search (df-pn).
+///
+/// Proof numbers are stored in negamax form: `phi` is the proof number of a win for the player to move,
+/// `delta` the proof number of a loss. The transposition table lives across calls, `clear` drops it.
+/// Moves are the empty cells left by the cutoffs, a position without moves is lost for the player to move.
+/// This only holds for sound cutoffs, with any other cutoff the search is a heuristic and `prove` gives None
+pub struct DfpnSolver {
+    pub config: DfpnSolverConfig,
+    cutoffs: Vec<Box<dyn Cutoff>>,
+    tt: HashMap<u64, Entry>,
+    nodes: usize,
+    deadline: Option<Instant>,
+}
+
+impl DfpnSolver {
+    /// solver with `MustplayCutoff`, the only sound cutoff
+    pub fn new(config: DfpnSolverConfig) -> DfpnSolver {
+        DfpnSolver::with_cutoffs(config, vec![Box::new(MustplayCutoff)])
+    }
+
+    pub fn with_cutoffs(config: DfpnSolverConfig, cutoffs: Vec<Box<dyn Cutoff>>) -> DfpnSolver {
+        DfpnSolver {
+            config,
+            cutoffs,
+            tt: HashMap::new(),
+            nodes: 0,
+            deadline: None,
+        }
+    }
+
+    /// whether every cutoff keeps a winning move, so that the results are proofs
+    pub fn is_sound(&self) -> bool {
+        self.cutoffs.iter().all(|x| x.is_sound())
+    }
+
+    pub fn clear(&mut self) {
+        self.tt.clear();
+    }
+
+    /// the winner of the position, None when the budget runs out first or the cutoffs are not sound
+    pub fn prove(&mut self, board: &HexBoard, next_player: Player) -> Option<Player> {
+        self.nodes = 0;
+        self.deadline = self.config.time_limit.map(|x| Instant::now() + x);
+        self.mid(board, next_player, INF, INF);
+        if !self.is_sound() {
+            return None;
+        }
+
+        let entry = &self.tt[&board.hash_with_player(next_player)];
+        if entry.phi == 0 {
+            Some(next_player)
+        } else if entry.delta == 0 {
+            Some(next_player.reverse())
+        } else {
+            None
+        }
+    }
+
+    fn out_of_budget(&self) -> bool {
+        if let Some(node_limit) = self.config.node_limit {
+            if self.nodes >= node_limit {
+                return true;
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn generate(&self, board: &HexBoard, next_player: Player) -> Entry {
+        if let Some(winner) = board.winner() {
+            let (phi, delta) = if winner == next_player { (0, INF) } else { (INF, 0) };
+            return Entry { phi, delta, moves: Vec::new() };
+        }
+
+        // cells cut by the sound cutoffs and by the others
+        let mut sound_cut = vec![false; board.cell_count()];
+        let mut cut = vec![false; board.cell_count()];
+        for cutoff in self.cutoffs.iter() {
+            let out = if cutoff.is_sound() { &mut sound_cut } else { &mut cut };
+            cutoff.cutoff(board, next_player, None, &mut out[..]);
+        }
+        let sound_moves: Vec<u16> = (0..board.cell_count())
+            .filter(|&i| !sound_cut[i] && board.get_abs(i) == CellState::Empty)
+            .map(|i| i as u16)
+            .collect();
+        let mut moves: Vec<u16> = sound_moves.iter().cloned().filter(|&i| !cut[i as usize]).collect();
+        // the other cutoffs may cut every move of a position that is not lost
+        if moves.is_empty() {
+            moves = sound_moves;
+        }
+
+        if moves.is_empty() {
+            return Entry { phi: INF, delta: 0, moves };
+        }
+        for &mov in moves.iter() {
+            let mut next = board.clone();
+            next.set_abs(mov as usize, next_player.to_cell());
+            if next.winner() == Some(next_player) {
+                return Entry { phi: 0, delta: INF, moves };
+            }
+        }
+
+        Entry { phi: 1, delta: 1, moves }
+    }
+
+    /// proof and disproof numbers of the child reached by `mov`, (1, 1) when it is not searched yet
+    fn child_numbers(&self, board: &HexBoard, next_player: Player, mov: u16) -> (u32, u32) {
+        let mut child = board.clone();
+        child.set_abs(mov as usize, next_player.to_cell());
+        match self.tt.get(&child.hash_with_player(next_player.reverse())) {
+            Some(x) => (x.phi, x.delta),
+            None => (1, 1)
+        }
+    }
+
+    /// multiple iterative deepening, search until a threshold is reached
+    fn mid(&mut self, board: &HexBoard, next_player: Player, phi_th: u32, delta_th: u32) {
+        let key = board.hash_with_player(next_player);
+        if !self.tt.contains_key(&key) {
+            let entry = self.generate(board, next_player);
+            self.tt.insert(key, entry);
+        }
+        self.nodes += 1;
+
+        loop {
+            let entry = &self.tt[&key];
+            if entry.phi == 0 || entry.delta == 0 {
+                return;
+            }
+            let moves = entry.moves.clone();
+
+            let mut phi = INF;
+            let mut delta: u32 = 0;
+            let mut best = 0;
+            let mut best_phi = 0;
+            let mut second_delta = INF;
+            for &mov in moves.iter() {
+                let (child_phi, child_delta) = self.child_numbers(board, next_player, mov);
+                if child_delta < phi {
+                    second_delta = phi;
+                    phi = child_delta;
+                    best = mov;
+                    best_phi = child_phi;
+                } else if child_delta < second_delta {
+                    second_delta = child_delta;
+                }
+                delta = if child_phi >= INF || delta >= INF { INF } else { (delta + child_phi).min(INF - 1) };
+            }
+
+            let entry = self.tt.get_mut(&key).unwrap();
+            entry.phi = phi;
+            entry.delta = delta;
+            if phi >= phi_th || delta >= delta_th || phi == 0 || delta == 0 || self.out_of_budget() {
+                return;
+            }
+
+            let child_phi_th = if delta_th >= INF {
+                INF
+            } else {
+                (delta_th - delta).saturating_add(best_phi).min(INF)
+            };
+            let child_delta_th = phi_th.min(second_delta.saturating_add(1));
+
+            let mut child = board.clone();
+            child.set_abs(best as usize, next_player.to_cell());
+            self.mid(&child, next_player.reverse(), child_phi_th, child_delta_th);
+        }
+    }
+
+    /// the winning child when the position is won, otherwise the child closest to a proof
+    fn best_move(&self, board: &HexBoard, next_player: Player) -> Option<u16> {
+        let entry = self.tt.get(&board.hash_with_player(next_player))?;
+        entry.moves.iter()
+            .min_by_key(|&&mov| self.child_numbers(board, next_player, mov).1)
+            .cloned()
+    }
+}
+
+impl Solver for DfpnSolver {
+    fn solve(&mut self, board: &HexBoard, next_player: Player) -> SearchReport {
+        let start = Instant::now();
+        let width = board.width;
+        let proven = self.prove(board, next_player);
+
+        let entry = &self.tt[&board.hash_with_player(next_player)];
+        let win_rate = match proven {
+            Some(x) if x == next_player => 1.0,
+            Some(_) => 0.0,
+            None => entry.delta as f64 / (entry.phi as f64 + entry.delta as f64),
+        };
+        let children = entry.moves.iter().map(|&mov| ChildReport {
+            mov: Cell::from_pos(mov as usize, width),
+            ..Default::default()
+        }).collect();
+
+        let mut principal_variation = Vec::new();
+        let mut current = board.clone();
+        let mut player = next_player;
+        while let Some(mov) = self.best_move(&current, player) {
+            if principal_variation.len() >= board.cell_count() {
+                break;
+            }
+            principal_variation.push(Cell::from_pos(mov as usize, width));
+            current.set_abs(mov as usize, player.to_cell());
+            player = player.reverse();
+        }
+
+        SearchReport {
+            best_move: principal_variation.first().cloned().unwrap_or_else(|| fallback_move(board, next_player)),
+            win_rate,
+            principal_variation,
+            children,
+            total_nodes: self.tt.len(),
+            total_expansion: self.nodes,
+            elapsed: start.elapsed(),
+            proven,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::cell::Cell;
+    use crate::common::player::Player;
+    use crate::cutoff::pattern_cutoff::PatternCutoff;
+    use crate::solver::dfpn_solver::{DfpnSolver, DfpnSolverConfig};
+    use crate::solver::solver::Solver;
+
+    #[test]
+    fn test_prove() {
+        let mut solver = DfpnSolver::new(Default::default());
+        assert_eq!(solver.prove(&HexBoard::new(3), Player::Red), Some(Player::Red));
+        assert_eq!(solver.prove(&HexBoard::new(4), Player::Blue), Some(Player::Blue));
+
+        let mut board = HexBoard::new(3);
+        board.set(1, 1, CellState::Red);
+        assert_eq!(solver.prove(&board, Player::Blue), Some(Player::Red));
+
+        let mut board = HexBoard::new(3);
+        board.set(0, 2, CellState::Blue);
+        board.set(2, 0, CellState::Blue);
+        board.set(1, 1, CellState::Blue);
+        assert_eq!(solver.prove(&board, Player::Red), Some(Player::Blue));
+
+        // the player with the shorter distance wins on an n x (n + 1) board, even moving second
+        assert_eq!(solver.prove(&HexBoard::new_rect(4, 3), Player::Blue), Some(Player::Red));
+        assert_eq!(solver.prove(&HexBoard::new_rect(3, 4), Player::Red), Some(Player::Blue));
+    }
+
+    #[test]
+    fn test_solve() {
+        let mut solver = DfpnSolver::new(Default::default());
+        let board = HexBoard::new(4);
+        let report = solver.solve(&board, Player::Red);
+        assert_eq!(report.proven, Some(Player::Red));
+        assert_eq!(report.win_rate, 1.0);
+
+        let Cell { x, y } = report.best_move;
+        let mut board = board.clone();
+        board.set(x, y, CellState::Red);
+        let mut solver = DfpnSolver::new(Default::default());
+        assert_eq!(solver.prove(&board, Player::Blue), Some(Player::Red));
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let config = DfpnSolverConfig {
+            node_limit: Some(3),
+            ..Default::default()
+        };
+        let mut solver = DfpnSolver::new(config);
+        assert_eq!(solver.prove(&HexBoard::new(6), Player::Red), None);
+    }
+
+    #[test]
+    fn test_cutoffs_agree_with_exact_search() {
+        let exact = |board: &HexBoard, player: Player| DfpnSolver::with_cutoffs(Default::default(), vec![]).prove(board, player);
+
+        for (s, player) in [("b3/4/4/3b", Player::Red), ("4/2r1/b3/1rr1", Player::Red)] {
+            let board: HexBoard = s.parse().unwrap();
+            assert_eq!(DfpnSolver::new(Default::default()).prove(&board, player), exact(&board, player));
+        }
+
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..20 {
+            let mut board = HexBoard::new(4);
+            for _ in 0..rng.gen_range(4..10) {
+                let cell = if rng.gen_bool(0.5) { CellState::Red } else { CellState::Blue };
+                board.set_abs(rng.gen_range(0..16), cell);
+            }
+            if board.winner().is_some() {
+                continue;
+            }
+            let player = if rng.gen_bool(0.5) { Player::Red } else { Player::Blue };
+            assert_eq!(DfpnSolver::new(Default::default()).prove(&board, player), exact(&board, player));
+        }
+
+        // the pattern cutoff is not sound, its searches are not proofs
+        let mut solver = DfpnSolver::with_cutoffs(Default::default(), vec![Box::new(PatternCutoff)]);
+        assert!(!solver.is_sound());
+        assert_eq!(solver.prove(&HexBoard::new(3), Player::Red), None);
+    }
+}
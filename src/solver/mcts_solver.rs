@@ -1,122 +1,49 @@
-use std::cell::{RefCell};
-use std::fmt::Display;
-use std::rc::{Rc, Weak};
-use smallvec::SmallVec;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
 use crate::common::board::{CellState, HexBoard};
-use crate::common::constants::MAX_SIZE;
+use crate::common::cell::Cell;
 use crate::common::player::Player;
 use crate::cutoff::cutoff::Cutoff;
 use crate::cutoff::inferior_cell_cutoff::InferiorCellCutoff;
 use crate::cutoff::two_distance_cutoff::TwoDistanceCutoff;
-use crate::inferior_cell::inferior_cell::fill_dead_and_captured;
 use crate::simulator::save_bridge_simulator::SaveBridgeSimulator;
 use crate::simulator::simulator::Simulator;
-use crate::solver::solver::Solver;
-use serde::Serialize;
+use crate::solver::mcts_tree::{Tree, DEFAULT_ARENA_CAPACITY};
+use crate::solver::mcts_tt::{TranspositionTable, TtReplacement};
+use crate::solver::report::{ChildReport, SearchReport};
+use crate::solver::solver::{fallback_move, Solver};
 use crate::cutoff::pattern_cutoff::PatternCutoff;
-use crate::prior::pattern_prior::PatternPrior;
 use crate::prior::prior::Prior;
 
-#[derive(Serialize)]
-struct Node {
-    pub visit: usize,
-    pub win: usize,
-    pub score: f64,
-    // pub amaf_win: [usize; MAX_SIZE * MAX_SIZE],
-    // pub amaf_vis: [usize; MAX_SIZE * MAX_SIZE],
-    pub amaf_win: usize,
-    pub amaf_vis: usize,
-
-    pub prior: f64,
-    #[serde(skip_serializing)]
-    pub board: HexBoard,
-    pub next_player: Player,
-    pub player: Player,
-    pub mov: usize,
-    pub depth: usize,
-    pub is_game_over_calculated: bool,
-    pub game_over: Option<Player>,
-    pub game_over_from_other_methods: Option<Player>,
-    pub size: usize,
-
-    // pub parent: Option<Rc<RefCell<Node>>>,
-    #[serde(skip_serializing)]
-    pub parent: Option<Weak<RefCell<Node>>>,
-    #[serde(skip_serializing)]
-    pub children: Vec<Rc<RefCell<Node>>>,
-    // pub children_count: usize,
+/// How the weight of the AMAF value goes from 1 to 0 as a node gets visited.
+/// `n` is the visit count, `ñ` the AMAF visit count and `k` is `amaf_constant`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AmafSchedule {
+    /// RAVE is not used
+    None,
+    /// `sqrt(k / (3n + k))`, from Gelly and Silver
+    GellySilver,
+    /// `ñ / (n + ñ + nñ / k)`, the AMAF value has the weight of `ñ` playouts until `n` approaches `k`, like MoHex
+    MoHex,
 }
 
-impl Node {
-    fn new(size: usize) -> Node {
-        Node {
-            visit: 0,
-            win: 0,
-            score: 0.0,
-            amaf_win: 0,
-            amaf_vis: 1,
-            prior: 0.0,
-            board: HexBoard::new(size),
-            next_player: Player::Red,
-            player: Player::Red,
-            mov: 0,
-            depth: 0,
-            is_game_over_calculated: false,
-            game_over: None,
-            game_over_from_other_methods: None,
-            size,
-            parent: None,
-            children: Vec::with_capacity(MAX_SIZE * MAX_SIZE),
-            // children_count: 0
+impl AmafSchedule {
+    pub fn weight(&self, k: f64, visit: f64, amaf_vis: f64) -> f64 {
+        if amaf_vis == 0.0 {
+            return 0.0;
         }
-    }
-    
-    fn expand_from_node(node: Rc<RefCell<Node>>, position: usize) -> Node {
-        let mut board = node.borrow().board.clone();
-        let player = node.borrow().next_player;
-        board.set_abs(position, player.to_cell());
-
-        Node {
-            visit: 0,
-            win: 0,
-            score: 0.0,
-            amaf_win: 0,
-            amaf_vis: 0,
-            prior: 0.0,
-            board,
-            next_player: player.reverse(),
-            player,
-            mov: position,
-            depth: node.borrow().depth + 1,
-            is_game_over_calculated: false,
-            game_over: node.borrow().game_over.clone(),
-            game_over_from_other_methods: node.borrow().game_over_from_other_methods.clone(),
-            size: node.borrow().size,
-            // parent: Some(node.clone()),
-            parent: Some(Rc::downgrade(&node)),
-            children: Vec::with_capacity(MAX_SIZE * MAX_SIZE)
-        }
-    }
 
-    fn has_children(&self) -> bool {
-        self.children.len() > 0
-    }
-
-    fn get_game_over(&mut self) -> Option<Player> {
-        if let Some(x) = self.game_over_from_other_methods {
-            return Some(x);
-        }
-        if self.is_game_over_calculated {
-            self.game_over.clone()
-        } else {
-            self.is_game_over_calculated = true;
-            self.game_over = self.board.winner();
-            self.game_over.clone()
+        match *self {
+            AmafSchedule::None => 0.0,
+            AmafSchedule::GellySilver => (k / (3.0 * visit + k)).sqrt(),
+            AmafSchedule::MoHex => amaf_vis / (visit + amaf_vis + visit * amaf_vis / k),
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MCTSSolverConfig {
     pub min_sim: usize,
     pub max_sim: usize,
@@ -124,9 +51,29 @@ pub struct MCTSSolverConfig {
     pub times_per_sim: usize,
 
     pub amaf_constant: f64,
+    pub amaf_schedule: AmafSchedule,
     pub ucb_constant: f64,
     pub pb_constant: f64,
     pub win_weight: f64,
+
+    /// keep the search tree between calls to `solve` and continue from the subtree of the new position
+    pub reuse_tree: bool,
+
+    /// stop the search after this much wall-clock time
+    pub time_limit: Option<Duration>,
+    /// stop the search once this many nodes are created
+    pub node_limit: Option<usize>,
+    /// remaining time on the game clock, the time for one move is allocated from it, see `time_for_move`
+    pub game_clock: Option<Duration>,
+
+    /// number of search threads sharing the tree
+    pub threads: usize,
+    /// maximum number of nodes in the tree, the search stops when the arena is full
+    pub arena_capacity: usize,
+
+    /// bytes of the transposition table sharing statistics between transpositions, None disables it
+    pub tt_memory: Option<usize>,
+    pub tt_replacement: TtReplacement,
 }
 
 impl Default for MCTSSolverConfig {
@@ -137,145 +84,193 @@ impl Default for MCTSSolverConfig {
             simulation_amount: 500000,
             times_per_sim: 5,
             amaf_constant: 500.0,
+            amaf_schedule: AmafSchedule::GellySilver,
             ucb_constant: 1.414,
             pb_constant: 2.0,
-            win_weight: 1.0
+            win_weight: 1.0,
+            reuse_tree: true,
+            time_limit: None,
+            node_limit: None,
+            game_clock: None,
+            threads: 1,
+            arena_capacity: DEFAULT_ARENA_CAPACITY,
+            tt_memory: None,
+            tt_replacement: TtReplacement::LeastVisited,
         }
     }
 }
 
-pub struct MCTSSolverHelper {
-    pub config: MCTSSolverConfig,
-
-    pub size: usize,
-    pub total_expand: usize,
-    pub total_cut: usize,
-    pub total_nodes: usize,
-    pub total_expansion: usize,
+/// time for one move when `remaining` is left on the clock, assuming the game can last
+/// until the board is full, so the clock never runs out
+pub fn time_for_move(remaining: Duration, empty_count: usize) -> Duration {
+    let moves_left = (empty_count as u32).div_ceil(2);
+    remaining / moves_left.max(1)
+}
 
-    // pub root: Rc<RefCell<Node>>,
-    pub simulator: Box<dyn Simulator>,
-    pub cutoffs: Vec<Box<dyn Cutoff>>,
-    pub prior: Option<Box<dyn Prior>>,
+pub struct MCTSSolverHelper<'a> {
+    pub config: MCTSSolverConfig,
+    pub deadline: Option<Instant>,
+
+    pub width: usize,
+    pub height: usize,
+    pub total_expand: AtomicUsize,
+    pub total_cut: AtomicUsize,
+    pub total_nodes: AtomicUsize,
+    pub total_expansion: AtomicUsize,
+
+    pub simulator: &'a dyn Simulator,
+    pub cutoffs: &'a [Box<dyn Cutoff>],
+    pub prior: Option<&'a dyn Prior>,
+    pub tt: Option<&'a TranspositionTable>,
 }
 
-impl MCTSSolverHelper {
-    fn get_amaf_weight(&self, node: Rc<RefCell<Node>>) -> f64 {
-        let k = self.config.amaf_constant;
-        let visit = node.borrow().visit as f64;
+/// the player who moved into the node at `depth` of the path from the root
+fn node_player(tree: &Tree, depth: usize) -> Player {
+    if depth.is_multiple_of(2) {
+        tree.next_player.reverse()
+    } else {
+        tree.next_player
+    }
+}
 
-        // (k / (3.0 * visit + k)).sqrt()
-        0.0
+impl<'a> MCTSSolverHelper<'a> {
+    fn get_amaf_weight(&self, visit: f64, amaf_vis: f64) -> f64 {
+        self.config.amaf_schedule.weight(self.config.amaf_constant, visit, amaf_vis)
     }
 
-    fn get_ucb(&self, node: Rc<RefCell<Node>>) -> f64 {
+    fn get_ucb(&self, parent_visit: f64, visit: f64) -> f64 {
         let k = self.config.ucb_constant;
-        // let parent_visit = node.borrow().parent.as_ref().unwrap().borrow().visit as f64;
-        let parent_visit = node.borrow().parent.as_ref().unwrap().upgrade().unwrap().borrow().visit as f64;
-        let visit = node.borrow().visit as f64;
 
         k * (parent_visit.ln() / visit).sqrt()
     }
 
-    fn get_pb_weight(&self, node: Rc<RefCell<Node>>) -> f64 {
+    fn get_pb_weight(&self, visit: f64) -> f64 {
         let k = self.config.pb_constant;
-        let visit = node.borrow().visit as f64;
         k / (visit + 1.0).sqrt()
     }
 
-    fn update_mcts(&self, node: Rc<RefCell<Node>>, red_win: usize, total: usize) {
-        let mut n: Rc<RefCell<Node>> = node;
-
-        loop {
-            // println!("{:?}", n.as_ptr());
-            if n.borrow().player == Player::Red {
-                n.borrow_mut().win += red_win;
+    /// back up the playout result along the path from the root and remove the virtual losses.
+    /// `hashes` are the positions of the path nodes, their transposition table entries are updated too
+    fn update_mcts(&self, tree: &Tree, path: &[u32], hashes: &[u64], red_win: usize, total: usize) {
+        for (depth, &id) in path.iter().enumerate().rev() {
+            let n = tree.node(id);
+            let win = if node_player(tree, depth) == Player::Red {
+                red_win
             } else {
-                n.borrow_mut().win += total - red_win;
-            }
-            n.borrow_mut().visit += total;
-            // println!("{}", n.borrow().visit);
-
-            let temp = match n.borrow().parent {
-                None => {
-                    // println!("none");
-                    break;
-                },
-                Some(ref x) => {
-                    x.upgrade().unwrap().clone()
-                }
+                total - red_win
             };
-            n = temp;
+            n.win.fetch_add(win as u32, Ordering::Relaxed);
+            n.visit.fetch_add(total as u32, Ordering::Relaxed);
+            n.virtual_loss.fetch_sub(total as u32, Ordering::Relaxed);
 
-            // n = nn.borrow().parent.clone();
-            // n = nn.borrow().parent.as_ref().unwrap().upgrade().clone();
+            if let Some(tt) = self.tt {
+                tt.update(hashes[depth], total as u32, win as u32);
+            }
         }
-        // println!("loop end");
     }
 
-    fn update_amaf(&self, node: Rc<RefCell<Node>>, red_win: usize, total: usize) {
-        let mut n = node;
-
-        // 1: red, 2: blue
-        let mut moves = [0; MAX_SIZE * MAX_SIZE];
-        let mut moves_count = 0;
-
-        loop {
-            let player = n.borrow().player;
-            // let mov = n.borrow().mov;
-            moves[moves_count] = if player == Player::Red { 1 } else { 2 };
-            moves_count += 1;
-
-            for i in 0..moves_count {
-                if moves[i] == 1 && player == Player::Red {
-                    n.borrow_mut().amaf_win += red_win;
-                } else if moves[i] == 2 && player == Player::Blue {
-                    n.borrow_mut().amaf_win += total - red_win;
+    /// all-moves-as-first update: for every node on the path, each child whose cell was filled
+    /// by the player to move at that node, in the tree or in the playout, counts `total` playouts.
+    /// `final_board` is the board at the end of the playouts
+    fn update_amaf(&self, tree: &Tree, path: &[u32], final_board: &HexBoard, winner: Player, total: usize) {
+        for (depth, &id) in path.iter().enumerate() {
+            let player = node_player(tree, depth).reverse();
+            let cell = player.to_cell();
+            let win = if winner == player { total } else { 0 };
+
+            for child in tree.node(id).children() {
+                let n = tree.node(child);
+                if final_board.get_abs(n.mov()) == cell {
+                    n.amaf_win.fetch_add(win as u32, Ordering::Relaxed);
+                    n.amaf_vis.fetch_add(total as u32, Ordering::Relaxed);
                 }
             }
-            n.borrow_mut().amaf_vis += total;
+        }
+    }
 
-            let temp = match n.borrow().parent {
-                None => break,
-                Some(ref x) => {
-                    x.upgrade().unwrap().clone()
-                }
+    /// MCTS-Solver backup: starting from the leaf of the path, a node is won by the player to move
+    /// if any child is won by them, and lost if every child is won by the opponent.
+    /// Losses are only backed up through nodes whose children are every move the sound cutoffs keep
+    fn update_proven(&self, tree: &Tree, path: &[u32]) {
+        for depth in (0..path.len() - 1).rev() {
+            let winner = match tree.node(path[depth + 1]).proven() {
+                Some(x) => x,
+                None => return
             };
-            n = temp;
+
+            let player = node_player(tree, depth).reverse();
+            let node = tree.node(path[depth]);
+            if winner == player {
+                node.set_game_over_from_other_methods(player);
+            } else if node.is_complete() && node.children().all(|x| tree.node(x).proven() == Some(player.reverse())) {
+                node.set_game_over_from_other_methods(player.reverse());
+            } else {
+                return;
+            }
         }
     }
 
-    fn select(&self, node: Rc<RefCell<Node>>) -> Rc<RefCell<Node>> {
-        let mut max_score = -1.0;
-        let mut max_index = 0;
+    /// select a child and add a virtual loss to it, so that other threads spread out
+    fn select(&self, tree: &Tree, id: u32, board: &HexBoard, player: Player) -> u32 {
+        let child = self.select_child(tree, id, board, player);
+        tree.node(child).virtual_loss.fetch_add(self.config.times_per_sim as u32, Ordering::Relaxed);
+        child
+    }
 
-        for (i, _) in node.borrow().children.iter().enumerate() {
-            let n = node.borrow().children[i].clone();
+    /// `board` is the position of `id` and `player` is the player to move there
+    fn select_child(&self, tree: &Tree, id: u32, board: &HexBoard, player: Player) -> u32 {
+        let mut max_score = f64::NEG_INFINITY;
+        let node = tree.node(id);
+        let children = node.children();
+        let mut max_index = children.start;
+
+        let parent_visit = (node.visit.load(Ordering::Relaxed) + node.virtual_loss.load(Ordering::Relaxed)) as f64;
+        for i in children {
+            let n = tree.node(i);
+            // proven children are not searched any more
+            match n.proven() {
+                Some(x) if x == player => return i,
+                Some(_) => continue,
+                None => {}
+            }
+            let visit = (n.visit.load(Ordering::Relaxed) + n.virtual_loss.load(Ordering::Relaxed)) as usize;
 
             // if simulation count < min sim, return the node
-            if n.borrow().visit < self.config.min_sim {
-                return n;
+            if visit < self.config.min_sim {
+                return i;
             }
 
-            let beta = self.get_amaf_weight(n.clone());
-            let ucb = self.get_ucb(n.clone());
-            let pb = self.get_pb_weight(n.clone());
-
+            let visit = visit as f64;
             let win_weight = self.config.win_weight;
-            let win_count = n.borrow().win as f64;
-            let visit = n.borrow().visit as f64;
+            let mut win_count = n.win.load(Ordering::Relaxed) as f64;
+            let mut value_visit = visit;
+            // UCT2: the value comes from every path to the position, the exploration from this edge
+            if let Some((tt_visit, tt_win)) = self.tt.and_then(|tt| tt.get(board.hash_after_move(n.mov(), player))) {
+                if tt_visit as f64 > visit {
+                    win_count = tt_win as f64;
+                    value_visit = tt_visit as f64;
+                }
+            }
 
-            let amaf_win = n.borrow().amaf_win as f64;
-            let amaf_vis = n.borrow().amaf_vis as f64;
+            let amaf_win = n.amaf_win.load(Ordering::Relaxed) as f64;
+            let amaf_vis = n.amaf_vis.load(Ordering::Relaxed) as f64;
 
-            let ucb_part = (win_count * win_weight - (1.0 - win_weight) * (visit - win_count)) / visit;
-            let amaf_part = (win_weight * amaf_win - (1.0 - win_weight) * (amaf_vis - amaf_win)) / amaf_vis;
+            let beta = self.get_amaf_weight(visit, amaf_vis);
+            let ucb = self.get_ucb(parent_visit, visit);
+            let pb = self.get_pb_weight(visit);
+
+            let ucb_part = (win_count * win_weight - (1.0 - win_weight) * (value_visit - win_count)) / value_visit;
+            let amaf_part = if amaf_vis > 0.0 {
+                (win_weight * amaf_win - (1.0 - win_weight) * (amaf_vis - amaf_win)) / amaf_vis
+            } else {
+                0.0
+            };
 
             let score = (1.0 - beta) * ucb_part + beta * amaf_part + ucb
             // let score = ucb_part
-                + pb * n.borrow().prior;
+                + pb * n.prior();
 
-            n.borrow_mut().score = score;
+            n.score.store((score as f32).to_bits(), Ordering::Relaxed);
 
             if score > max_score {
                 max_score = score;
@@ -283,166 +278,269 @@ impl MCTSSolverHelper {
             }
         }
 
-        node.borrow().children[max_index].clone()
+        max_index
     }
 
-    /// return false: no children is available, meaning node is eventually a winning status
-    fn expand(&mut self, node: Rc<RefCell<Node>>) -> bool {
-        let ss = self.size * self.size;
-        let size = self.size;
+    /// return false: no children is available, meaning node is eventually a winning status.
+    /// When the arena is full the node stays a leaf and true is returned
+    fn expand(&self, tree: &Tree, id: u32, board: &HexBoard, next_player: Player) -> bool {
+        let ss = self.width * self.height;
+        let width = self.width;
+        let node = tree.node(id);
 
-        let mut cutoffs = [false; MAX_SIZE * MAX_SIZE];
+        // cells cut by the sound cutoffs and by the others
+        let mut sound_cutoffs = vec![false; ss];
+        let mut cutoffs = vec![false; ss];
 
-        {
-            let board = &node.borrow().board;
-            let next_player = node.borrow().next_player;
-            let last_move = if node.borrow().mov >= ss {
-                None
-            } else {
-                let temp = node.borrow().mov;
-                let x = temp / size;
-                let y = temp % size;
-                Some((x, y))
-            };
-            for cutoff in self.cutoffs.iter() {
-                cutoff.cutoff(&board, next_player, last_move, &mut cutoffs[..]);
-            }
+        let last_move = if node.mov() >= ss {
+            None
+        } else {
+            let temp = node.mov();
+            let x = temp / width;
+            let y = temp % width;
+            Some((x, y))
+        };
+        for cutoff in self.cutoffs.iter() {
+            let out = if cutoff.is_sound() { &mut sound_cutoffs } else { &mut cutoffs };
+            cutoff.cutoff(board, next_player, last_move, &mut out[..]);
         }
 
-        for i in 0..ss {
-            if !cutoffs[i] && node.borrow().board.get_abs(i) == CellState::Empty {
-                self.total_expand += 1;
-
-                let new_node = Rc::new(RefCell::new(Node::expand_from_node(node.clone(), i)));
-                // let board_with_dead_and_captured = fill_dead_and_captured(
-                //     &new_node.borrow().board,
-                //     new_node.borrow().next_player
-                // );
-                // new_node.borrow_mut().board = board_with_dead_and_captured;
-                node.borrow_mut().children.push(new_node.clone());
+        let sound_moves: Vec<usize> = (0..ss)
+            .filter(|&i| !sound_cutoffs[i] && board.get_abs(i) == CellState::Empty)
+            .collect();
+        let mut moves: Vec<usize> = sound_moves.iter().cloned().filter(|&i| !cutoffs[i]).collect();
+        // the other cutoffs may cut every move of a position that is not lost
+        if moves.is_empty() {
+            moves = sound_moves.clone();
+        }
+        let complete = moves.len() == sound_moves.len();
 
-                self.total_nodes += 1;
+        let first = match tree.arena.alloc(moves.len()) {
+            Some(x) => x,
+            None => {
+                node.abort_expand();
+                return true;
             }
-        }
+        };
 
-        self.calc_prior(node.clone());
+        let prior = self.calc_prior(board, last_move, next_player);
+        for (i, &mov) in moves.iter().enumerate() {
+            tree.node(first + i as u32).init(mov as u16, prior[mov] as f32);
+        }
+        node.finish_expand(first, moves.len(), complete);
 
-        self.total_expansion += 1;
+        self.total_expand.fetch_add(moves.len(), Ordering::Relaxed);
+        self.total_nodes.fetch_add(moves.len(), Ordering::Relaxed);
+        self.total_expansion.fetch_add(1, Ordering::Relaxed);
 
-        node.borrow().children.len() > 0
+        !moves.is_empty()
     }
 
-    fn calc_prior(&self, node: Rc<RefCell<Node>>) {
-        if self.prior.is_none() {
-            return;
+    fn calc_prior(&self, board: &HexBoard, last_move: Option<(usize, usize)>, next_player: Player) -> Vec<f64> {
+        let mut out = vec![0.0; board.cell_count()];
+        if let Some(prior) = self.prior {
+            prior.prior(board, last_move, next_player, &mut out[..]);
         }
 
-        let board = &node.borrow().board;
-        let size = board.size;
-        let mov = node.borrow().mov;
-        let last_move = if mov < usize::MAX {
-            Some((mov / size, mov % size))
-        } else {
-            None
-        };
-        let next_player = node.borrow().next_player;
+        out
+    }
 
-        let prior = self.prior.as_ref().unwrap();
-        let mut out = [0.0; MAX_SIZE * MAX_SIZE];
-        prior.prior(&board, last_move, next_player, &mut out[..]);
+    fn out_of_budget(&self, tree: &Tree) -> bool {
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return true;
+            }
+        }
+        if let Some(node_limit) = self.config.node_limit {
+            if self.total_nodes.load(Ordering::Relaxed) >= node_limit {
+                return true;
+            }
+        }
+        if tree.arena.used() + self.width * self.height > tree.arena.capacity() {
+            return true;
+        }
 
-        for child in node.borrow().children.iter() {
-            let mov = child.borrow().mov;
-            child.borrow_mut().prior = out[mov];
+        false
+    }
+
+    fn uct(&self, tree: &Tree) {
+        if self.config.threads <= 1 {
+            self.uct_worker(tree);
+        } else {
+            std::thread::scope(|s| {
+                for _ in 0..self.config.threads {
+                    s.spawn(|| self.uct_worker(tree));
+                }
+            });
         }
     }
 
-    fn uct(&mut self, node: Rc<RefCell<Node>>) {
-        while node.borrow().visit < self.config.simulation_amount {
-            let mut n = node.clone();
-            while n.borrow().has_children() {
-                n = self.select(n.clone());
+    fn uct_worker(&self, tree: &Tree) {
+        let times_per_sim = self.config.times_per_sim;
+        let root = tree.root_node();
+
+        while (root.visit.load(Ordering::Relaxed) as usize) < self.config.simulation_amount
+            && root.proven().is_none()
+            && !self.out_of_budget(tree) {
+            root.virtual_loss.fetch_add(times_per_sim as u32, Ordering::Relaxed);
+            let mut board = tree.board.clone();
+            let mut next_player = tree.next_player;
+            let mut path = vec![tree.root];
+            let mut hashes = vec![board.hash_with_player(next_player)];
+            let mut n = tree.root;
+            while tree.node(n).has_children() && tree.node(n).proven().is_none() {
+                n = self.select(tree, n, &board, next_player);
+                board.set_abs(tree.node(n).mov(), next_player.to_cell());
+                next_player = next_player.reverse();
+                path.push(n);
+                hashes.push(board.hash_with_player(next_player));
             }
 
-            let visit = n.borrow().visit;
+            let node = tree.node(n);
+            let visit = node.visit.load(Ordering::Relaxed) as usize;
             if visit >= self.config.max_sim {
-                let is_game_over = n.borrow_mut().get_game_over().is_some();
-                if !is_game_over {
-                    if !self.expand(n.clone()) {
+                let is_game_over = node.get_game_over(&board).is_some();
+                // only one thread expands a node, the others keep simulating from the leaf
+                if !is_game_over && node.try_start_expand() {
+                    if !self.expand(tree, n, &board, next_player) {
                         // cannot expand, the game is actually over
-                        let player = n.borrow().player;
-                        n.borrow_mut().game_over_from_other_methods = Some(player);
-                    } else {
-                        n = self.select(n.clone());
+                        node.set_game_over_from_other_methods(next_player.reverse());
+                    } else if node.has_children() {
+                        n = self.select(tree, n, &board, next_player);
+                        board.set_abs(tree.node(n).mov(), next_player.to_cell());
+                        next_player = next_player.reverse();
+                        path.push(n);
+                        hashes.push(board.hash_with_player(next_player));
                     }
                 }
             }
 
             let mut red_win = 0;
-            let game_over = n.borrow_mut().get_game_over();
+            let game_over = tree.node(n).get_game_over(&board);
             if let Some(x) = game_over {
-                red_win = if x == Player::Red { self.config.times_per_sim } else { 0 };
+                if x == Player::Red {
+                    red_win = times_per_sim;
+                }
+                self.update_amaf(tree, &path, &board, x, times_per_sim);
             } else {
                 // not game over
-                red_win = self.simulator.simulate(&n.borrow().board, n.borrow().next_player, self.config.times_per_sim);
+                for _ in 0..times_per_sim {
+                    let (winner, final_board) = self.simulator.playout(&board, next_player);
+                    if winner == Player::Red {
+                        red_win += 1;
+                    }
+                    self.update_amaf(tree, &path, &final_board, winner, 1);
+                }
             }
 
             // update mcts
-            self.update_mcts(n.clone(), red_win, self.config.times_per_sim);
-            // update amaf
-            self.update_amaf(n.clone(), red_win, self.config.times_per_sim);
+            self.update_mcts(tree, &path, &hashes, red_win, times_per_sim);
+            self.update_proven(tree, &path);
         }
     }
 }
 
 
-fn get_most_visited_children(node: Rc<RefCell<Node>>) -> Rc<RefCell<Node>> {
+fn get_most_visited_children(tree: &Tree, id: u32) -> u32 {
     let mut max_visit = 0;
-    let mut max_index = 0_usize;
-    for (index, item) in node.borrow().children.iter().enumerate() {
-        // let x = serde_json::to_string_pretty(&*item.borrow()).unwrap();
-        // println!("{}", x);
-        // println!("visit of {}: {}", item.borrow().mov, item.borrow().visit);
-        if item.borrow().visit > max_visit {
-            max_visit = item.borrow().visit;
-            max_index = index;
+    let children = tree.node(id).children();
+    let mut max_index = children.start;
+    for i in children {
+        let visit = tree.node(i).visit.load(Ordering::Relaxed);
+        if visit > max_visit {
+            max_visit = visit;
+            max_index = i;
         }
     }
 
-    node.borrow().children[max_index].clone()
+    max_index
 }
 
-pub struct MCTSSolver {
-    config: MCTSSolverConfig
-}
-
-impl MCTSSolver {
-    pub fn new(config: MCTSSolverConfig) -> Self {
-        Self {
-            config
+/// a child proven to be won by `player`, who is to move at `id`, otherwise the most visited child
+fn get_best_children(tree: &Tree, id: u32, player: Player) -> u32 {
+    for i in tree.node(id).children() {
+        if tree.node(i).proven() == Some(player) {
+            return i;
         }
     }
+
+    get_most_visited_children(tree, id)
 }
 
-impl Solver for MCTSSolver {
-    fn solve(&self, board: &HexBoard, next_player: Player) -> (usize, usize) {
-        let mut root = Node::new(board.size);
+/// follow the best children from the root
+fn principal_variation(tree: &Tree) -> Vec<Cell> {
+    let width = tree.board.width;
+    let mut result = Vec::new();
+    let mut id = tree.root;
+    let mut player = tree.next_player;
+    while tree.node(id).has_children() {
+        id = get_best_children(tree, id, player);
+        player = player.reverse();
+        let mov = tree.node(id).mov();
+        result.push(Cell::from_pos(mov, width));
+    }
+
+    result
+}
 
-        root.board = board.clone();
-        root.player = next_player.reverse();
-        root.next_player = next_player;
-        root.mov = usize::MAX;
+fn make_report(tree: &Tree, helper: &MCTSSolverHelper, elapsed: Duration) -> SearchReport {
+    let width = tree.board.width;
+    let root = tree.root_node();
+
+    let children = root.children().map(|id| {
+        let n = tree.node(id);
+        let mov = n.mov();
+        ChildReport {
+            mov: Cell::from_pos(mov, width),
+            visit: n.visit.load(Ordering::Relaxed) as usize,
+            win: n.win.load(Ordering::Relaxed) as usize,
+            prior: n.prior(),
+            amaf_win: n.amaf_win.load(Ordering::Relaxed) as usize,
+            amaf_vis: n.amaf_vis.load(Ordering::Relaxed) as usize,
+        }
+    }).collect();
+
+    let visit = root.visit.load(Ordering::Relaxed) as f64;
+    let proven = root.get_game_over(&tree.board);
+    // root wins are counted for the player who moved into the root
+    let win_rate = if let Some(x) = proven {
+        if x == tree.next_player { 1.0 } else { 0.0 }
+    } else if visit > 0.0 {
+        1.0 - root.win.load(Ordering::Relaxed) as f64 / visit
+    } else {
+        0.5
+    };
+    let principal_variation = principal_variation(tree);
+
+    SearchReport {
+        best_move: principal_variation.first().cloned().unwrap_or_else(|| fallback_move(&tree.board, tree.next_player)),
+        win_rate,
+        principal_variation,
+        children,
+        total_expand: helper.total_expand.load(Ordering::Relaxed),
+        total_nodes: helper.total_nodes.load(Ordering::Relaxed),
+        total_expansion: helper.total_expansion.load(Ordering::Relaxed),
+        elapsed,
+        proven,
+    }
+}
 
-        let root = Rc::new(RefCell::new(root));
+/// Builds an `MCTSSolver` with custom components.
+/// The defaults are `SaveBridgeSimulator`, the cutoffs
+/// `[InferiorCellCutoff, TwoDistanceCutoff { rank: 4 }, PatternCutoff]` and no prior.
+/// `MustplayCutoff` runs H-search on every expansion, add it with `add_cutoff` when
+/// the smaller trees are worth the slower search.
+pub struct MCTSSolverBuilder {
+    config: MCTSSolverConfig,
+    simulator: Box<dyn Simulator>,
+    cutoffs: Vec<Box<dyn Cutoff>>,
+    prior: Option<Box<dyn Prior>>,
+}
 
-        let mut helper = MCTSSolverHelper {
-            config: self.config.clone(),
-            size: board.size,
-            total_expand: 0,
-            total_cut: 0,
-            total_nodes: 0,
-            total_expansion: 0,
-            // root: root.clone(),
+impl Default for MCTSSolverBuilder {
+    fn default() -> Self {
+        MCTSSolverBuilder {
+            config: Default::default(),
             simulator: Box::new(SaveBridgeSimulator),
             cutoffs: vec![
                 Box::new(InferiorCellCutoff),
@@ -451,13 +549,483 @@ impl Solver for MCTSSolver {
             ],
             // prior: Some(Box::new(PatternPrior))
             prior: None
+        }
+    }
+}
+
+impl MCTSSolverBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn config(mut self, config: MCTSSolverConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn simulator(mut self, simulator: Box<dyn Simulator>) -> Self {
+        self.simulator = simulator;
+        self
+    }
+
+    /// replace the cutoffs, they are applied in order
+    pub fn cutoffs(mut self, cutoffs: Vec<Box<dyn Cutoff>>) -> Self {
+        self.cutoffs = cutoffs;
+        self
+    }
+
+    pub fn add_cutoff(mut self, cutoff: Box<dyn Cutoff>) -> Self {
+        self.cutoffs.push(cutoff);
+        self
+    }
+
+    pub fn prior(mut self, prior: Option<Box<dyn Prior>>) -> Self {
+        self.prior = prior;
+        self
+    }
+
+    pub fn build(self) -> MCTSSolver {
+        MCTSSolver {
+            config: self.config,
+            simulator: self.simulator,
+            cutoffs: self.cutoffs,
+            prior: self.prior,
+            tree: None,
+            tt: None,
+        }
+    }
+}
+
+pub struct MCTSSolver {
+    config: MCTSSolverConfig,
+    simulator: Box<dyn Simulator>,
+    cutoffs: Vec<Box<dyn Cutoff>>,
+    prior: Option<Box<dyn Prior>>,
+    tree: Option<Tree>,
+    tt: Option<TranspositionTable>,
+}
+
+impl MCTSSolver {
+    /// solver with the default components, see `MCTSSolverBuilder`
+    pub fn new(config: MCTSSolverConfig) -> Self {
+        MCTSSolverBuilder::new().config(config).build()
+    }
+
+    pub fn config(&self) -> &MCTSSolverConfig {
+        &self.config
+    }
+
+    /// change the configuration between searches, the tree kept for `reuse_tree` stays
+    pub fn config_mut(&mut self) -> &mut MCTSSolverConfig {
+        &mut self.config
+    }
+
+    /// set the time left on the game clock before the next search, see `game_clock`
+    pub fn set_remaining_time(&mut self, remaining: Option<Duration>) {
+        self.config.game_clock = remaining;
+    }
+
+    /// the tree of the last search
+    pub fn tree(&self) -> Option<&Tree> {
+        self.tree.as_ref()
+    }
+
+    pub fn builder() -> MCTSSolverBuilder {
+        MCTSSolverBuilder::new()
+    }
+
+    /// drop the tree kept from the previous search
+    pub fn clear(&mut self) {
+        self.tree = None;
+        self.tt = None;
+    }
+
+    /// continue with the subtree of the position when it is in the old tree, otherwise start a new tree
+    fn take_tree(&mut self, board: &HexBoard, next_player: Player) -> Tree {
+        if let Some(tree) = self.tree.take().filter(|_| self.config.reuse_tree) {
+            match tree.find(board, next_player) {
+                Some(id) if id == tree.root => return tree,
+                Some(id) => return tree.extract(id, board, next_player),
+                None => {}
+            }
+        }
+
+        Tree::new(board, next_player, self.config.arena_capacity)
+    }
+}
+
+impl Solver for MCTSSolver {
+    fn solve(&mut self, board: &HexBoard, next_player: Player) -> SearchReport {
+        let tree = self.take_tree(board, next_player);
+
+        let start = Instant::now();
+        let time_limit = match (self.config.time_limit, self.config.game_clock) {
+            (Some(x), Some(clock)) => Some(x.min(time_for_move(clock, board.empty_count))),
+            (Some(x), None) => Some(x),
+            (None, Some(clock)) => Some(time_for_move(clock, board.empty_count)),
+            (None, None) => None,
+        };
+
+        if self.config.tt_memory.is_none() {
+            self.tt = None;
+        } else if self.tt.is_none() {
+            self.tt = self.config.tt_memory.map(|x| TranspositionTable::new(x, self.config.tt_replacement));
+        }
+
+        let helper = MCTSSolverHelper {
+            config: self.config.clone(),
+            deadline: time_limit.map(|x| start + x),
+            width: board.width,
+            height: board.height,
+            total_expand: AtomicUsize::new(0),
+            total_cut: AtomicUsize::new(0),
+            total_nodes: AtomicUsize::new(0),
+            total_expansion: AtomicUsize::new(0),
+            simulator: self.simulator.as_ref(),
+            cutoffs: &self.cutoffs,
+            prior: self.prior.as_deref(),
+            tt: self.tt.as_ref(),
+        };
+        helper.uct(&tree);
+
+        let report = make_report(&tree, &helper, start.elapsed());
+        self.tree = Some(tree);
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::cell::Cell;
+    use crate::common::player::Player;
+    use crate::cutoff::cutoff::Cutoff;
+    use crate::cutoff::mustplay_cutoff::MustplayCutoff;
+    use crate::prior::pattern_prior::PatternPrior;
+    use crate::simulator::simulator::Simulator;
+    use crate::solver::mcts_solver::{get_most_visited_children, time_for_move, AmafSchedule, MCTSSolver, MCTSSolverConfig};
+    use crate::solver::mcts_tree::Node;
+    use crate::solver::report::SearchReport;
+    use crate::solver::solver::Solver;
+
+    struct RedWinsSimulator;
+
+    impl Simulator for RedWinsSimulator {
+        fn simulate_once(&self, _board: &HexBoard, _next_player: Player) -> Player {
+            Player::Red
+        }
+    }
+
+    #[test]
+    fn test_reuse_tree() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 2000,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let mut board = HexBoard::new(4);
+
+        let Cell { x, y } = solver.solve(&board, Player::Red).best_move;
+        board.set(x, y, Player::Red.to_cell());
+        let tree = solver.tree.as_ref().unwrap();
+        let child = get_most_visited_children(tree, tree.root);
+        let grandchild = get_most_visited_children(tree, child);
+        let visit = tree.node(grandchild).visit.load(Ordering::Relaxed);
+        board.set_abs(tree.node(grandchild).mov(), Player::Blue.to_cell());
+
+        let tree = solver.take_tree(&board, Player::Red);
+        assert_eq!(tree.root_node().visit.load(Ordering::Relaxed), visit);
+        assert!(tree.board == board);
+        assert!(tree.arena.used() < 2000);
+
+        let tree = solver.take_tree(&HexBoard::new(5), Player::Red);
+        assert_eq!(tree.root_node().visit.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_budget() {
+        let config = MCTSSolverConfig {
+            node_limit: Some(200),
+            reuse_tree: false,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let report = solver.solve(&HexBoard::new(5), Player::Red);
+        // the limit is checked before each expansion, which adds at most one node per cell
+        assert!(report.total_nodes >= 200 && report.total_nodes < 200 + 25);
+
+        let config = MCTSSolverConfig {
+            time_limit: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+        // the limit is checked between playouts, the slack is for slow machines
+        let mut solver = MCTSSolver::new(config);
+        let report = solver.solve(&HexBoard::new(11), Player::Red);
+        assert!(report.elapsed >= Duration::from_millis(100));
+        assert!(report.elapsed < Duration::from_secs(2));
+
+        // 1.22s for the 61 moves left of red gives 20ms for this move,
+        // far below the default 500000 playouts
+        let mut solver = MCTSSolver::new(Default::default());
+        solver.set_remaining_time(Some(Duration::from_millis(1220)));
+        let board = HexBoard::new(11);
+        let report = solver.solve(&board, Player::Red);
+        assert!(report.elapsed >= Duration::from_millis(20));
+        assert!(report.elapsed < Duration::from_secs(2));
+        assert!(report.total_expansion < 500000);
+
+        // changing the clock keeps the tree
+        let visit = solver.tree().unwrap().root_node().visit.load(Ordering::Relaxed);
+        solver.set_remaining_time(Some(Duration::from_millis(600)));
+        solver.solve(&board, Player::Red);
+        assert!(solver.tree().unwrap().root_node().visit.load(Ordering::Relaxed) > visit);
+        assert_eq!(solver.config().game_clock, Some(Duration::from_millis(600)));
+
+        let config = MCTSSolverConfig {
+            arena_capacity: 1000,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        solver.solve(&HexBoard::new(5), Player::Red);
+        assert!(solver.tree.unwrap().arena.used() <= 1000);
+    }
+
+    #[test]
+    fn test_no_time() {
+        let config = MCTSSolverConfig {
+            time_limit: Some(Duration::from_nanos(1)),
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let mut board = HexBoard::new(5);
+        board.set(0, 0, CellState::Red);
+        board.set(2, 2, CellState::Blue);
+
+        let report = solver.solve(&board, Player::Red);
+        assert!(report.children.is_empty());
+        assert!(board.is_empty(report.best_move.x, report.best_move.y));
+    }
+
+    #[test]
+    fn test_threads() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 5000,
+            threads: 4,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let Cell { x, y } = solver.solve(&HexBoard::new(5), Player::Red).best_move;
+        assert!(x < 5 && y < 5);
+
+        let tree = solver.tree.unwrap();
+        let root = tree.root_node();
+        let visit = root.visit.load(Ordering::Relaxed);
+        assert!(visit >= 5000);
+        assert_eq!(root.virtual_loss.load(Ordering::Relaxed), 0);
+        let children_visit: u32 = root.children().map(|x| tree.node(x).visit.load(Ordering::Relaxed)).sum();
+        assert!(children_visit <= visit);
+    }
+
+    #[test]
+    fn test_report() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 3000,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let report = solver.solve(&HexBoard::new(4), Player::Red);
+
+        assert_eq!(report.principal_variation[0], report.best_move);
+        assert!(report.win_rate >= 0.0 && report.win_rate <= 1.0);
+        assert!(report.total_nodes > 0 && report.total_expansion > 0);
+        assert_eq!(report.proven, None);
+
+        let max_visit = report.children.iter().map(|x| x.visit).max().unwrap();
+        let best = report.children.iter().find(|x| x.mov == report.best_move).unwrap();
+        assert_eq!(best.visit, max_visit);
+        let visit: usize = report.children.iter().map(|x| x.visit).sum();
+        assert!(visit <= 3000 + 5);
+    }
+
+    #[test]
+    fn test_serde() {
+        let config: MCTSSolverConfig = serde_json::from_str(r#"{"simulation_amount": 2000, "amaf_schedule": "MoHex"}"#).unwrap();
+        assert_eq!(config.simulation_amount, 2000);
+        assert_eq!(config.amaf_schedule, AmafSchedule::MoHex);
+        assert_eq!(config.threads, MCTSSolverConfig::default().threads);
+
+        let mut solver = MCTSSolver::new(config);
+        let report = solver.solve(&HexBoard::new(4), Player::Red);
+        let json = serde_json::to_string(&report).unwrap();
+        let parsed: SearchReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.best_move, report.best_move);
+        assert_eq!(parsed.children.len(), report.children.len());
+
+        let tree = solver.tree.as_ref().unwrap();
+        let dump = tree.dump(1);
+        assert_eq!(dump.mov, None);
+        assert_eq!(dump.children.len(), tree.root_node().children().len());
+        assert!(dump.children.iter().all(|x| x.mov.is_some() && x.children.is_empty()));
+        assert!(tree.dump(0).children.is_empty());
+        let child = dump.children.iter().find(|x| x.mov == Some(report.best_move)).unwrap();
+        assert_eq!(child.visit as usize, report.children.iter().find(|x| x.mov == report.best_move).unwrap().visit);
+        assert!(serde_json::to_value(tree.dump(2)).unwrap()["children"][0]["children"].is_array());
+    }
+
+    #[test]
+    fn test_amaf() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 3000,
+            amaf_schedule: AmafSchedule::MoHex,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let report = solver.solve(&HexBoard::new(4), Player::Red);
+
+        // every playout through a child fills its cell, so it is also an AMAF playout of the child
+        for child in report.children.iter() {
+            assert!(child.amaf_vis >= child.visit);
+            assert!(child.amaf_win <= child.amaf_vis);
+        }
+        let visit: usize = report.children.iter().map(|x| x.visit).sum();
+        let amaf_vis: usize = report.children.iter().map(|x| x.amaf_vis).sum();
+        assert!(amaf_vis > visit);
+    }
+
+    #[test]
+    fn test_amaf_schedule() {
+        assert_eq!(AmafSchedule::None.weight(500.0, 10.0, 100.0), 0.0);
+        assert_eq!(AmafSchedule::GellySilver.weight(500.0, 10.0, 0.0), 0.0);
+        assert_eq!(AmafSchedule::GellySilver.weight(500.0, 0.0, 100.0), 1.0);
+        assert!((AmafSchedule::GellySilver.weight(500.0, 500.0, 100.0) - 0.5).abs() < 1e-9);
+        assert_eq!(AmafSchedule::MoHex.weight(500.0, 0.0, 100.0), 1.0);
+        assert!((AmafSchedule::MoHex.weight(500.0, 100.0, 100.0) - 100.0 / 220.0).abs() < 1e-9);
+        assert!(AmafSchedule::MoHex.weight(500.0, 10000.0, 20000.0) < 0.05);
+    }
+
+    /// an unsound cutoff that keeps only (0, 0) on an empty board
+    struct AcuteCornerCutoff;
+
+    impl Cutoff for AcuteCornerCutoff {
+        fn cutoff(&self, board: &HexBoard, _next_player: Player, _last_move: Option<(usize, usize)>, out: &mut [bool]) {
+            if board.empty_count == board.cell_count() {
+                out[1..board.cell_count()].fill(true);
+            }
+        }
+    }
+
+    #[test]
+    fn test_proven() {
+        // red wins at once with (2, 0)
+        let mut board = HexBoard::new(3);
+        board.set(0, 0, CellState::Red);
+        board.set(1, 0, CellState::Red);
+        board.set(0, 1, CellState::Blue);
+        board.set(1, 1, CellState::Blue);
+        let mut solver = MCTSSolver::new(Default::default());
+        let report = solver.solve(&board, Player::Red);
+
+        assert_eq!(report.proven, Some(Player::Red));
+        assert_eq!(report.best_move, Cell::new(2, 0));
+        assert_eq!(report.win_rate, 1.0);
+        let visit: usize = report.children.iter().map(|x| x.visit).sum();
+        assert!(visit < 1000);
+
+        // red holds the center of 3x3 and wins whatever blue plays. The default cutoffs are not sound,
+        // the mustplay cutoff leaves no move for blue so the root is lost at once
+        let mut board = HexBoard::new(3);
+        board.set(1, 1, CellState::Red);
+        let mut solver = MCTSSolver::new(Default::default());
+        let report = solver.solve(&board, Player::Blue);
+        assert_ne!(report.proven, Some(Player::Blue));
+
+        let mut solver = MCTSSolver::builder()
+            .add_cutoff(Box::new(MustplayCutoff))
+            .build();
+        let report = solver.solve(&board, Player::Blue);
+        assert_eq!(report.proven, Some(Player::Red));
+        assert_eq!(report.win_rate, 0.0);
+        assert!(solver.tree().unwrap().root_node().children().is_empty());
+
+        // only the losing acute corner is left at the root of 2x2, its loss says nothing of the root
+        let mut solver = MCTSSolver::builder()
+            .cutoffs(vec![Box::new(AcuteCornerCutoff)])
+            .build();
+        let report = solver.solve(&HexBoard::new(2), Player::Red);
+        assert_eq!(report.children.len(), 1);
+        let tree = solver.tree().unwrap();
+        assert_eq!(tree.node(tree.root_node().children().start).proven(), Some(Player::Blue));
+        assert_eq!(report.proven, None);
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 5000,
+            tt_memory: Some(1 << 20),
+            ..Default::default()
         };
-        helper.uct(root.clone());
-        println!("done");
+        let mut solver = MCTSSolver::new(config);
+        let board = HexBoard::new(4);
+        solver.solve(&board, Player::Red);
+
+        let tree = solver.tree.as_ref().unwrap();
+        let tt = solver.tt.as_ref().unwrap();
+        let root_visit = tree.root_node().visit.load(Ordering::Relaxed);
+        assert_eq!(tt.get(board.hash_with_player(Player::Red)), Some((root_visit, tree.root_node().win.load(Ordering::Relaxed))));
+
+        // positions three moves deep are reached in more than one order
+        let mut shared = false;
+        for a in tree.root_node().children() {
+            for b in tree.node(a).children() {
+                for c in tree.node(b).children() {
+                    let mut position = board.clone();
+                    position.set_abs(tree.node(a).mov(), CellState::Red);
+                    position.set_abs(tree.node(b).mov(), CellState::Blue);
+                    position.set_abs(tree.node(c).mov(), CellState::Red);
+                    let visit = tree.node(c).visit.load(Ordering::Relaxed);
+                    if let Some((tt_visit, _)) = tt.get(position.hash_with_player(Player::Blue)) {
+                        assert!(tt_visit >= visit);
+                        shared |= tt_visit > visit;
+                    }
+                }
+            }
+        }
+        assert!(shared);
+    }
 
-        let most_visited_node = get_most_visited_children(root.clone());
-        let mov = most_visited_node.borrow().mov;
+    #[test]
+    fn test_builder() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 2000,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::builder()
+            .config(config)
+            .simulator(Box::new(RedWinsSimulator))
+            .cutoffs(vec![])
+            .prior(Some(Box::new(PatternPrior)))
+            .build();
+        let report = solver.solve(&HexBoard::new(4), Player::Red);
+
+        assert_eq!(report.children.len(), 16);
+        assert!(report.win_rate > 0.99);
+        let prior: f64 = report.children.iter().map(|x| x.prior).sum();
+        assert!((prior - 1.0).abs() < 1e-3);
+    }
 
-        (mov / board.size, mov % board.size)
+    #[test]
+    fn test_node_size() {
+        assert!(std::mem::size_of::<Node>() <= 48);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_time_for_move() {
+        assert_eq!(time_for_move(Duration::from_secs(60), 119), Duration::from_secs(1));
+        assert_eq!(time_for_move(Duration::from_secs(60), 1), Duration::from_secs(60));
+        assert_eq!(time_for_move(Duration::from_secs(60), 0), Duration::from_secs(60));
+    }
+}
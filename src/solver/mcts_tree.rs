@@ -0,0 +1,356 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+use crate::common::board::HexBoard;
+use crate::common::cell::Cell;
+use crate::common::player::Player;
+
+const CHUNK_BITS: usize = 16;
+const CHUNK_SIZE: usize = 1 << CHUNK_BITS;
+
+/// capacity of the arena when the search has no node limit
+pub const DEFAULT_ARENA_CAPACITY: usize = 1 << 26;
+
+pub const NO_MOVE: u16 = u16::MAX;
+
+const LEAF: u8 = 0;
+const EXPANDING: u8 = 1;
+const EXPANDED: u8 = 2;
+
+// encoding of Option<Player> in the game over fields, 0 means not calculated
+const NO_WINNER: u8 = 1;
+const RED_WINS: u8 = 2;
+const BLUE_WINS: u8 = 3;
+
+fn encode_winner(winner: Option<Player>) -> u8 {
+    match winner {
+        None => NO_WINNER,
+        Some(Player::Red) => RED_WINS,
+        Some(Player::Blue) => BLUE_WINS,
+    }
+}
+
+fn decode_winner(value: u8) -> Option<Player> {
+    match value {
+        RED_WINS => Some(Player::Red),
+        BLUE_WINS => Some(Player::Blue),
+        _ => None
+    }
+}
+
+/// A search tree node stored in the arena.
+///
+/// Nodes hold only their move and statistics, the board is replayed from the root board while descending.
+/// Every field is atomic so that the search threads can share the arena; the children of a node
+/// are a consecutive range of the arena published by the thread that expands it.
+#[derive(Default)]
+pub struct Node {
+    pub visit: AtomicU32,
+    pub win: AtomicU32,
+    /// pending playouts of other threads, counted as losses while selecting
+    pub virtual_loss: AtomicU32,
+    /// bits of the f32 score computed by the last `select`
+    pub score: AtomicU32,
+    pub amaf_win: AtomicU32,
+    pub amaf_vis: AtomicU32,
+    /// bits of the f32 prior
+    pub prior: AtomicU32,
+
+    pub mov: AtomicU16,
+    pub child_count: AtomicU16,
+    pub first_child: AtomicU32,
+    pub state: AtomicU8,
+    pub game_over: AtomicU8,
+    pub game_over_from_other_methods: AtomicU8,
+    /// whether the children are every move left by the sound cutoffs,
+    /// only then the node is lost when all of them are
+    pub complete: AtomicBool,
+}
+
+impl Node {
+    pub fn init(&self, mov: u16, prior: f32) {
+        self.visit.store(0, Ordering::Relaxed);
+        self.win.store(0, Ordering::Relaxed);
+        self.virtual_loss.store(0, Ordering::Relaxed);
+        self.score.store(0, Ordering::Relaxed);
+        self.amaf_win.store(0, Ordering::Relaxed);
+        self.amaf_vis.store(0, Ordering::Relaxed);
+        self.prior.store(prior.to_bits(), Ordering::Relaxed);
+        self.mov.store(mov, Ordering::Relaxed);
+        self.child_count.store(0, Ordering::Relaxed);
+        self.first_child.store(0, Ordering::Relaxed);
+        self.state.store(LEAF, Ordering::Relaxed);
+        self.game_over.store(0, Ordering::Relaxed);
+        self.game_over_from_other_methods.store(0, Ordering::Relaxed);
+        self.complete.store(false, Ordering::Relaxed);
+    }
+
+    fn copy_from(&self, other: &Node) {
+        self.visit.store(other.visit.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.win.store(other.win.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.virtual_loss.store(0, Ordering::Relaxed);
+        self.score.store(other.score.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.amaf_win.store(other.amaf_win.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.amaf_vis.store(other.amaf_vis.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.prior.store(other.prior.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.mov.store(other.mov.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.child_count.store(0, Ordering::Relaxed);
+        self.first_child.store(0, Ordering::Relaxed);
+        self.state.store(LEAF, Ordering::Relaxed);
+        self.game_over.store(other.game_over.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.game_over_from_other_methods.store(other.game_over_from_other_methods.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.complete.store(false, Ordering::Relaxed);
+    }
+
+    pub fn mov(&self) -> usize {
+        self.mov.load(Ordering::Relaxed) as usize
+    }
+
+    pub fn prior(&self) -> f64 {
+        f32::from_bits(self.prior.load(Ordering::Relaxed)) as f64
+    }
+
+    pub fn score(&self) -> f64 {
+        f32::from_bits(self.score.load(Ordering::Relaxed)) as f64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::Relaxed)
+    }
+
+    pub fn has_children(&self) -> bool {
+        self.state.load(Ordering::Acquire) == EXPANDED && self.child_count.load(Ordering::Relaxed) > 0
+    }
+
+    /// claim the node for expansion, only one thread succeeds
+    pub fn try_start_expand(&self) -> bool {
+        self.state.compare_exchange(LEAF, EXPANDING, Ordering::Acquire, Ordering::Relaxed).is_ok()
+    }
+
+    pub fn abort_expand(&self) {
+        self.state.store(LEAF, Ordering::Release);
+    }
+
+    pub fn finish_expand(&self, first_child: u32, child_count: usize, complete: bool) {
+        self.first_child.store(first_child, Ordering::Relaxed);
+        self.child_count.store(child_count as u16, Ordering::Relaxed);
+        self.complete.store(complete, Ordering::Relaxed);
+        self.state.store(EXPANDED, Ordering::Release);
+    }
+
+    pub fn children(&self) -> std::ops::Range<u32> {
+        if self.state.load(Ordering::Acquire) != EXPANDED {
+            return 0..0;
+        }
+        let first = self.first_child.load(Ordering::Relaxed);
+        first..first + self.child_count.load(Ordering::Relaxed) as u32
+    }
+
+    /// winner of the node, `board` is the position of the node
+    pub fn get_game_over(&self, board: &HexBoard) -> Option<Player> {
+        let other = self.game_over_from_other_methods.load(Ordering::Relaxed);
+        if other != 0 {
+            return decode_winner(other);
+        }
+
+        let value = self.game_over.load(Ordering::Relaxed);
+        if value != 0 {
+            decode_winner(value)
+        } else {
+            let winner = board.winner();
+            self.game_over.store(encode_winner(winner), Ordering::Relaxed);
+            winner
+        }
+    }
+
+    /// winner of the node if it is already known, without looking at the board
+    pub fn proven(&self) -> Option<Player> {
+        let other = self.game_over_from_other_methods.load(Ordering::Relaxed);
+        if other != 0 {
+            return decode_winner(other);
+        }
+
+        decode_winner(self.game_over.load(Ordering::Relaxed))
+    }
+
+    pub fn set_game_over_from_other_methods(&self, winner: Player) {
+        self.game_over_from_other_methods.store(encode_winner(Some(winner)), Ordering::Relaxed);
+    }
+}
+
+/// Node storage indexed by `u32` ids.
+/// Chunks are allocated on demand, so that threads allocate nodes with one atomic increment.
+pub struct Arena {
+    chunks: Vec<OnceLock<Box<[Node]>>>,
+    len: AtomicUsize,
+    capacity: usize,
+}
+
+impl Arena {
+    pub fn new(capacity: usize) -> Arena {
+        let chunk_count = capacity.div_ceil(CHUNK_SIZE);
+        Arena {
+            chunks: (0..chunk_count).map(|_| OnceLock::new()).collect(),
+            len: AtomicUsize::new(0),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, id: u32) -> &Node {
+        let id = id as usize;
+        &self.chunks[id >> CHUNK_BITS].get().unwrap()[id & (CHUNK_SIZE - 1)]
+    }
+
+    /// reserve `count` consecutive nodes, None when the arena is full
+    pub fn alloc(&self, count: usize) -> Option<u32> {
+        let start = self.len.fetch_add(count, Ordering::Relaxed);
+        if start + count > self.capacity {
+            return None;
+        }
+
+        if count > 0 {
+            for chunk in (start >> CHUNK_BITS)..=((start + count - 1) >> CHUNK_BITS) {
+                self.chunks[chunk].get_or_init(|| (0..CHUNK_SIZE).map(|_| Node::default()).collect());
+            }
+        }
+
+        Some(start as u32)
+    }
+
+    pub fn used(&self) -> usize {
+        self.len.load(Ordering::Relaxed).min(self.capacity)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// A copy of the top of a search tree that can be serialized, see `Tree::dump`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TreeDump {
+    /// the move leading to the node, None for the root
+    pub mov: Option<Cell>,
+    pub visit: u32,
+    /// wins of the player who plays `mov`
+    pub win: u32,
+    pub prior: f64,
+    /// the score computed by the last `select`
+    pub score: f64,
+    pub amaf_win: u32,
+    pub amaf_vis: u32,
+    /// the winner, when the search proved the node
+    pub proven: Option<Player>,
+    pub children: Vec<TreeDump>,
+}
+
+/// The search tree of one position: the arena, the root node and the board of the root.
+pub struct Tree {
+    pub arena: Arena,
+    pub root: u32,
+    pub board: HexBoard,
+    pub next_player: Player,
+}
+
+impl Tree {
+    pub fn new(board: &HexBoard, next_player: Player, capacity: usize) -> Tree {
+        let arena = Arena::new(capacity.max(1));
+        let root = arena.alloc(1).unwrap();
+        let node = arena.get(root);
+        node.init(NO_MOVE, 0.0);
+
+        Tree {
+            arena,
+            root,
+            board: board.clone(),
+            next_player,
+        }
+    }
+
+    pub fn node(&self, id: u32) -> &Node {
+        self.arena.get(id)
+    }
+
+    pub fn root_node(&self) -> &Node {
+        self.arena.get(self.root)
+    }
+
+    /// the node reached from the root by playing the position `board`, looking at most two moves ahead
+    pub fn find(&self, board: &HexBoard, next_player: Player) -> Option<u32> {
+        if next_player == self.next_player && *board == self.board {
+            return Some(self.root);
+        }
+
+        for child in self.root_node().children() {
+            let mut child_board = self.board.clone();
+            child_board.set_abs(self.node(child).mov(), self.next_player.to_cell());
+            if next_player != self.next_player && child_board == *board {
+                return Some(child);
+            }
+            if next_player != self.next_player {
+                continue;
+            }
+
+            for grandchild in self.node(child).children() {
+                let mut grandchild_board = child_board.clone();
+                grandchild_board.set_abs(self.node(grandchild).mov(), self.next_player.reverse().to_cell());
+                if grandchild_board == *board {
+                    return Some(grandchild);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// the nodes up to `max_depth` moves below the root, 0 gives only the root
+    pub fn dump(&self, max_depth: usize) -> TreeDump {
+        self.dump_node(self.root, max_depth)
+    }
+
+    fn dump_node(&self, id: u32, depth: usize) -> TreeDump {
+        let node = self.node(id);
+        let mov = node.mov.load(Ordering::Relaxed);
+        TreeDump {
+            mov: if mov == NO_MOVE { None } else { Some(Cell::from_pos(mov as usize, self.board.width)) },
+            visit: node.visit.load(Ordering::Relaxed),
+            win: node.win.load(Ordering::Relaxed),
+            prior: node.prior(),
+            score: node.score(),
+            amaf_win: node.amaf_win.load(Ordering::Relaxed),
+            amaf_vis: node.amaf_vis.load(Ordering::Relaxed),
+            proven: node.proven(),
+            children: if depth == 0 {
+                Vec::new()
+            } else {
+                node.children().map(|child| self.dump_node(child, depth - 1)).collect()
+            },
+        }
+    }
+
+    /// copy the subtree of `id` into a new tree, the rest of the arena is dropped with `self`
+    pub fn extract(&self, id: u32, board: &HexBoard, next_player: Player) -> Tree {
+        let result = Tree::new(board, next_player, self.arena.capacity());
+        result.root_node().copy_from(self.node(id));
+
+        let mut queue = VecDeque::new();
+        queue.push_back((id, result.root));
+        while let Some((from, to)) = queue.pop_front() {
+            let children = self.node(from).children();
+            let child_count = children.len();
+            if child_count == 0 {
+                continue;
+            }
+
+            let first = result.arena.alloc(child_count).unwrap();
+            for (i, child) in children.enumerate() {
+                result.node(first + i as u32).copy_from(self.node(child));
+                queue.push_back((child, first + i as u32));
+            }
+            result.node(to).finish_expand(first, child_count, self.node(from).is_complete());
+        }
+
+        result
+    }
+}
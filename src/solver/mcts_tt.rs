@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use serde::{Deserialize, Serialize};
+
+/// Which entry of a full bucket a new position takes
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TtReplacement {
+    /// the entry chosen by the hash, whatever it holds
+    Always,
+    /// the entry with fewer visits
+    LeastVisited,
+}
+
+const BUCKET_SIZE: usize = 2;
+
+#[derive(Default)]
+struct Slot {
+    /// hash of the position with the player to move, 0 for an empty slot
+    key: AtomicU64,
+    visit: AtomicU32,
+    /// wins of the player who moved into the position, like `Node::win`
+    win: AtomicU32,
+}
+
+/// Statistics shared by every node of the same position, so that the tree is searched like a DAG.
+///
+/// The table has a fixed number of two-entry buckets, positions that do not fit replace
+/// other positions according to `TtReplacement`. Threads update the entries without locks,
+/// a racing replacement may mix the statistics of two positions, which only adds noise to the search
+pub struct TranspositionTable {
+    slots: Vec<Slot>,
+    replacement: TtReplacement,
+}
+
+impl TranspositionTable {
+    /// a table using about `memory` bytes
+    pub fn new(memory: usize, replacement: TtReplacement) -> TranspositionTable {
+        let bucket_count = (memory / (std::mem::size_of::<Slot>() * BUCKET_SIZE)).max(1);
+        TranspositionTable {
+            slots: (0..bucket_count * BUCKET_SIZE).map(|_| Slot::default()).collect(),
+            replacement,
+        }
+    }
+
+    fn bucket(&self, hash: u64) -> &[Slot] {
+        let index = (hash % (self.slots.len() / BUCKET_SIZE) as u64) as usize * BUCKET_SIZE;
+        &self.slots[index..index + BUCKET_SIZE]
+    }
+
+    /// visits and wins of the position
+    pub fn get(&self, hash: u64) -> Option<(u32, u32)> {
+        self.bucket(hash).iter()
+            .find(|x| x.key.load(Ordering::Relaxed) == hash)
+            .map(|x| (x.visit.load(Ordering::Relaxed), x.win.load(Ordering::Relaxed)))
+    }
+
+    /// add the result of `visit` playouts to the position, replacing an entry if it is not in the table
+    pub fn update(&self, hash: u64, visit: u32, win: u32) {
+        let bucket = self.bucket(hash);
+        if let Some(slot) = bucket.iter().find(|x| x.key.load(Ordering::Relaxed) == hash) {
+            slot.visit.fetch_add(visit, Ordering::Relaxed);
+            slot.win.fetch_add(win, Ordering::Relaxed);
+            return;
+        }
+
+        let slot = match bucket.iter().find(|x| x.key.load(Ordering::Relaxed) == 0) {
+            Some(x) => x,
+            None => match self.replacement {
+                TtReplacement::Always => &bucket[(hash >> 32) as usize % BUCKET_SIZE],
+                TtReplacement::LeastVisited => bucket.iter().min_by_key(|x| x.visit.load(Ordering::Relaxed)).unwrap(),
+            }
+        };
+        slot.key.store(hash, Ordering::Relaxed);
+        slot.visit.store(visit, Ordering::Relaxed);
+        slot.win.store(win, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.key.store(0, Ordering::Relaxed);
+            slot.visit.store(0, Ordering::Relaxed);
+            slot.win.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::solver::mcts_tt::{TranspositionTable, TtReplacement};
+
+    #[test]
+    fn test_replacement() {
+        // a single bucket
+        let tt = TranspositionTable::new(32, TtReplacement::LeastVisited);
+        assert_eq!(tt.capacity(), 2);
+
+        tt.update(1, 10, 4);
+        tt.update(1, 5, 1);
+        tt.update(2, 3, 3);
+        assert_eq!(tt.get(1), Some((15, 5)));
+        assert_eq!(tt.get(2), Some((3, 3)));
+
+        tt.update(3, 1, 0);
+        assert_eq!(tt.get(1), Some((15, 5)));
+        assert_eq!(tt.get(2), None);
+        assert_eq!(tt.get(3), Some((1, 0)));
+
+        let tt = TranspositionTable::new(32, TtReplacement::Always);
+        tt.update(1, 10, 4);
+        tt.update(2, 3, 3);
+        tt.update(3, 1, 0);
+        assert_eq!(tt.get(3), Some((1, 0)));
+        assert!(tt.get(1).is_none() || tt.get(2).is_none());
+
+        tt.clear();
+        assert_eq!(tt.get(3), None);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod solver;
+pub mod report;
 pub mod mcts_solver;
+pub mod mcts_tree;pub mod dfpn_solver;
+pub mod alpha_beta_solver;
+pub mod mcts_tt;
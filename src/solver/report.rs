@@ -0,0 +1,34 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::common::cell::Cell;
+use crate::common::player::Player;
+
+/// statistics of one move at the root of the search
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChildReport {
+    pub mov: Cell,
+    pub visit: usize,
+    /// wins of the player who plays the move
+    pub win: usize,
+    pub prior: f64,
+    pub amaf_win: usize,
+    pub amaf_vis: usize,
+}
+
+/// result of one search, solvers fill in the fields that apply to them
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SearchReport {
+    pub best_move: Cell,
+    /// estimated winning probability of the player to move
+    pub win_rate: f64,
+    /// the expected line of play, starting with `best_move`
+    pub principal_variation: Vec<Cell>,
+    pub children: Vec<ChildReport>,
+
+    pub total_expand: usize,
+    pub total_nodes: usize,
+    pub total_expansion: usize,
+    pub elapsed: Duration,
+    /// the winner, when the search proved the position
+    pub proven: Option<Player>,
+}
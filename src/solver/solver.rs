@@ -1,6 +1,34 @@
-use crate::common::board::HexBoard;
+use crate::common::board::{CellState, HexBoard};
+use crate::common::cell::Cell;
+use crate::common::moves::Move;
 use crate::common::player::Player;
+use crate::opening::swap::opening_move;
+use crate::solver::report::SearchReport;
 
 pub trait Solver {
-    fn solve(&self, board: &HexBoard, next_player: Player) -> (usize, usize);
+    fn solve(&mut self, board: &HexBoard, next_player: Player) -> SearchReport;
+
+    /// solve under the swap rule: red opens with a move that is unlikely to be swapped,
+    /// and blue swaps when the first move is too strong
+    fn solve_with_swap(&mut self, board: &HexBoard, next_player: Player) -> Move {
+        if let Some(mov) = opening_move(board, next_player) {
+            return mov;
+        }
+
+        Move::Place(self.solve(board, next_player).best_move)
+    }
+}
+
+/// the move to report when a search ends before it finds one, e.g. out of time before the root
+/// is expanded: the empty cell with the best pattern score, so that the move is always legal.
+/// A full board has no legal move and gives `a1`
+pub fn fallback_move(board: &HexBoard, next_player: Player) -> Cell {
+    let mut score = vec![0.0; board.cell_count()];
+    board.pattern_score(&mut score[..], None, next_player);
+
+    (0..board.cell_count())
+        .filter(|&i| board.get_abs(i) == CellState::Empty)
+        .max_by(|&a, &b| score[a].total_cmp(&score[b]).then(b.cmp(&a)))
+        .map(|i| Cell::from_pos(i, board.width))
+        .unwrap_or_default()
 }
\ No newline at end of file
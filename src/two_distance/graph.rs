@@ -1,4 +1,3 @@
-use crate::common::constants::MAX_SIZE;
 use std::iter::Iterator;
 
 #[derive(Copy, Clone)]
@@ -7,32 +6,31 @@ pub struct Edge {
     pub to: usize,
 }
 
-const MAX_EDGE: usize = 2000;
-
 pub struct Graph {
-    pub head: [i32; MAX_SIZE * MAX_SIZE],
-    pub edges: [Edge; MAX_EDGE],
-    pub edge_count: usize,
+    pub head: Vec<i32>,
+    pub edges: Vec<Edge>,
 }
 
 impl Graph {
-    pub fn new() -> Graph {
+    /// a graph of `node_count` nodes without edges
+    pub fn new(node_count: usize) -> Graph {
         Graph {
-            head: [-1; MAX_SIZE * MAX_SIZE],
-            edges: [Edge { next: -1, to: 0 }; MAX_EDGE],
-            edge_count: 0
+            head: vec![-1; node_count],
+            edges: Vec::new(),
         }
     }
 
+    pub fn node_count(&self) -> usize {
+        self.head.len()
+    }
+
     pub fn add_edge(&mut self, from: usize, to: usize) {
         let e = self.head[from];
-        self.edges[self.edge_count].next = e;
-        self.edges[self.edge_count].to = to;
-        self.head[from] = self.edge_count as i32;
-        self.edge_count += 1;
+        self.edges.push(Edge { next: e, to });
+        self.head[from] = self.edges.len() as i32 - 1;
     }
 
-    pub fn iter_edge(&self, point: usize) -> EdgeIterator {
+    pub fn iter_edge(&self, point: usize) -> EdgeIterator<'_> {
         EdgeIterator {
             graph: &self,
             current: self.head[point]
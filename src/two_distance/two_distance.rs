@@ -1,23 +1,24 @@
 use std::collections::VecDeque;
-use crate::common::board::{CellState, HexBoard};
-use crate::common::constants::{DIS1, MAX_SIZE};
+use crate::common::bit_board::BitBoard;
+use crate::common::board::HexBoard;
+use crate::common::cell_set::CellSet;
 use crate::common::player;
 use crate::common::player::Player;
 use crate::two_distance::graph::Graph;
 
-const ARR_SIZE: usize = MAX_SIZE * MAX_SIZE + 4;
-
-pub fn two_distance(g: &Graph, start: usize, size: usize, out: &mut[usize]) {
-    let mut dis = [0x3f3f3f3f; ARR_SIZE];
-    let mut sub_dis = [0x3f3f3f3f; ARR_SIZE];
-    let mut two_dis = [0x3f3f3f3f; ARR_SIZE];
-    let mut vis = [false; ARR_SIZE];
+/// two distance from `start` to the first `count` nodes of the graph
+pub fn two_distance(g: &Graph, start: usize, count: usize, out: &mut[usize]) {
+    let node_count = g.node_count();
+    let mut dis = vec![0x3f3f3f3f; node_count];
+    let mut sub_dis = vec![0x3f3f3f3f; node_count];
+    let mut two_dis = vec![0x3f3f3f3f; node_count];
+    let mut vis = vec![false; node_count];
 
     dis[start] = 0;
     sub_dis[start] = 0;
     two_dis[start] = 0;
 
-    let mut q = VecDeque::with_capacity(ARR_SIZE);
+    let mut q = VecDeque::with_capacity(node_count);
     vis[start] = true;
     q.push_back(start);
 
@@ -61,9 +62,7 @@ pub fn two_distance(g: &Graph, start: usize, size: usize, out: &mut[usize]) {
     }
     // println!("two dis fin");
 
-    for i in 0..size * size {
-        out[i] = two_dis[i];
-    }
+    out[..count].copy_from_slice(&two_dis[..count]);
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -76,15 +75,15 @@ pub enum TwoDistanceStart {
 
 impl HexBoard {
     pub fn two_distance(&self, start: TwoDistanceStart, out: &mut [usize]) {
-        let size = self.size;
+        let count = self.cell_count();
 
         let g = self.get_reduced_graph(start);
-        two_distance(&g, size * size, size, &mut out[..]);
+        two_distance(&g, count, count, &mut out[..]);
     }
 
-    pub fn two_distance_sum(&self, player: Player) -> [usize; MAX_SIZE * MAX_SIZE] {
-        let mut d1 = [0; MAX_SIZE * MAX_SIZE];
-        let mut d2 = [0; MAX_SIZE * MAX_SIZE];
+    pub fn two_distance_sum(&self, player: Player) -> Vec<usize> {
+        let mut d1 = vec![0; self.cell_count()];
+        let mut d2 = vec![0; self.cell_count()];
         if player == Player::Red {
             self.two_distance(TwoDistanceStart::LeftTop, &mut d1);
             self.two_distance(TwoDistanceStart::RightBottom, &mut d2);
@@ -93,110 +92,68 @@ impl HexBoard {
             self.two_distance(TwoDistanceStart::RightTop, &mut d2);
         }
 
-        for i in 0..self.size * self.size {
-            d1[i] += d2[i];
+        for (a, b) in d1.iter_mut().zip(d2.iter()) {
+            *a += b;
         }
 
         d1
     }
 
+    pub fn get_reduced_graph(&self, start: TwoDistanceStart) -> Graph {
+        BitBoard::from(self).get_reduced_graph(start)
+    }
+
+    pub fn get_reduced_graph_without_border(&self, player: Player) -> Graph {
+        BitBoard::from(self).get_reduced_graph_without_border(player)
+    }
+}
+
+impl BitBoard {
+    /// the reduced graph with the node `width * height` for the edge `start`
     pub fn get_reduced_graph(&self, start: TwoDistanceStart) -> Graph {
         let target_player = match start {
             TwoDistanceStart::LeftTop | TwoDistanceStart::RightBottom => Player::Red,
             _ => Player::Blue
         };
-        let target_cell = target_player.to_cell();
+        let masks = self.masks();
+        let line = match start {
+            TwoDistanceStart::LeftTop => masks.first_row,
+            TwoDistanceStart::LeftBottom => masks.first_col,
+            TwoDistanceStart::RightTop => masks.last_col,
+            TwoDistanceStart::RightBottom => masks.last_row,
+        };
 
         let mut g = self.get_reduced_graph_without_border(target_player);
-        let mut vis = [[false; MAX_SIZE]; MAX_SIZE];
-        let size = self.size;
-        let ss = size * size;
-        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
-
-        for i in 0..size {
-            let (x, y) = match start {
-                TwoDistanceStart::LeftTop => (0, i),
-                TwoDistanceStart::LeftBottom => (i, 0),
-                TwoDistanceStart::RightTop => (i, size - 1),
-                TwoDistanceStart::RightBottom => (size - 1, i),
-            };
-            let pos = x * size + y;
-
-            let cell = self.get(x, y);
-            if cell == CellState::Empty {
-                g.add_edge(ss, pos);
-                g.add_edge(pos, ss);
-                vis[x][y] = true;
-            } else if cell == target_cell {
-                queue.push_back((x, y));
-            }
-        }
-
-        while !queue.is_empty() {
-            let (x, y) = queue.pop_front().unwrap();
-            for i in 0..6 {
-                let (nx, ny) = (x as i32 + DIS1[i][0], y as i32 + DIS1[i][1]);
-                if nx >= 0 && nx < size as i32 && ny >= 0 && ny < size as i32 {
-                    let (nx, ny) = (nx as usize, ny as usize);
-
-                    let cell = self.get(nx, ny);
-                    if cell == CellState::Empty {
-                        if !vis[nx][ny] {
-                            vis[nx][ny] = true;
-                            g.add_edge(nx * size + ny, ss);
-                            g.add_edge(ss, nx * size + ny);
-                        }
-                    } else if cell == target_cell {
-                        if !vis[nx][ny] {
-                            vis[nx][ny] = true;
-                            queue.push_back((nx, ny));
-                        }
-                    }
-                }
-            }
+        let ss = self.width * self.height;
+        let empty = self.empty();
+        let stones = self.stones(target_player);
+
+        // the edge reaches the empty cells of its line and the empty cells next to the groups touching it
+        let groups = self.flood(line, stones);
+        let reached = (line | self.neighbors(groups)) & empty;
+        for pos in reached.iter() {
+            g.add_edge(ss, pos);
+            g.add_edge(pos, ss);
         }
 
         g
     }
 
+    /// the graph of the empty cells, two cells are neighbors when they are adjacent
+    /// or both adjacent to a group of `player`
     pub fn get_reduced_graph_without_border(&self, player: Player) -> Graph {
-        let size = self.size;
-        let mut g = Graph::new();
-        let target_cell = player.to_cell();
-
-        for x in 0..size {
-            for y in 0..size {
-                if !self.is_empty(x, y) {
-                    continue;
-                }
-
-                let mut vis = [[false; MAX_SIZE]; MAX_SIZE];
-                let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
-                queue.push_back((x, y));
-                vis[x][y] = true;
-
-                while !queue.is_empty() {
-                    let (px, py) = queue.pop_front().unwrap();
-                    for d in 0..6 {
-                        let (nx, ny) = (px as i32 + DIS1[d][0], py as i32 + DIS1[d][1]);
-
-                        if nx >= 0 && nx < size as i32 && ny >= 0 && ny < size as i32 {
-                            let (nx, ny) = (nx as usize, ny as usize);
-                            let cell = self.get(nx, ny);
-                            if cell == CellState::Empty {
-                                if !vis[nx][ny] {
-                                    vis[nx][ny] = true;
-                                    g.add_edge(x * size + y, nx * size + ny);
-                                }
-                            } else if cell == target_cell {
-                                if !vis[nx][ny] {
-                                    vis[nx][ny] = true;
-                                    queue.push_back((nx, ny));
-                                }
-                            }
-                        }
-                    }
-                }
+        let mut g = Graph::new(self.width * self.height + 1);
+        let empty = self.empty();
+        let stones = self.stones(player);
+
+        for pos in empty.iter() {
+            let mut cell = CellSet::new();
+            cell.insert(pos);
+
+            let groups = self.flood(self.neighbors(cell), stones);
+            let reached = self.neighbors(cell | groups) & (empty - cell);
+            for to in reached.iter() {
+                g.add_edge(pos, to);
             }
         }
 
@@ -0,0 +1,348 @@
+use std::collections::{HashMap, VecDeque};
+use serde::{Deserialize, Serialize};
+use crate::common::board::{CellState, HexBoard};
+use crate::common::cell_set::CellSet;
+use crate::common::constants::DIS1;
+use crate::common::player::Player;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VcConfig {
+    /// maximum number of full connections kept between two nodes
+    pub max_full: usize,
+    /// maximum number of semi connections kept between two nodes
+    pub max_semi: usize,
+}
+
+impl Default for VcConfig {
+    fn default() -> Self {
+        VcConfig {
+            max_full: 8,
+            max_semi: 16,
+        }
+    }
+}
+
+/// A virtual connection between two nodes.
+/// A full connection holds against any opponent move, a semi connection becomes full
+/// when the player moves at `key`. The carrier holds the empty cells the connection needs,
+/// including the key
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Connection {
+    pub carrier: CellSet,
+    pub key: Option<usize>,
+}
+
+/// Virtual connections of one player, computed by H-search.
+///
+/// The nodes are the empty cells, the groups of the player and the two edges of the player.
+/// Empty cells are identified by their position `x * width + y`, a group by the position of its first cell,
+/// and the edges by `width * height` and `width * height + 1`, see `edge`.
+/// Connections are built from adjacent nodes with the AND rule, which joins two full connections
+/// through a common node, and the OR rule, which joins semi connections whose carriers do not intersect
+pub struct VcSet {
+    pub player: Player,
+    pub width: usize,
+    pub height: usize,
+    pub config: VcConfig,
+
+    nodes: Vec<Option<usize>>,
+    stones: CellSet,
+    full: HashMap<(usize, usize), Vec<Connection>>,
+    semi: HashMap<(usize, usize), Vec<Connection>>,
+    /// other ends of the full connections of each node
+    neighbors: Vec<Vec<usize>>,
+}
+
+fn key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+impl VcSet {
+    pub fn compute(board: &HexBoard, player: Player) -> VcSet {
+        VcSet::compute_with_config(board, player, Default::default())
+    }
+
+    pub fn compute_with_config(board: &HexBoard, player: Player, config: VcConfig) -> VcSet {
+        let (width, height) = (board.width, board.height);
+        let ss = board.cell_count();
+
+        let mut vc = VcSet {
+            player,
+            width,
+            height,
+            config,
+            nodes: vec![None; ss],
+            stones: CellSet::new(),
+            full: HashMap::new(),
+            semi: HashMap::new(),
+            neighbors: vec![Vec::new(); ss + 2],
+        };
+        vc.find_nodes(board);
+
+        let mut queue = VecDeque::new();
+        for pos in 0..ss {
+            let a = match vc.nodes[pos] {
+                Some(x) => x,
+                None => continue
+            };
+            let (x, y) = ((pos / width) as i32, (pos % width) as i32);
+
+            for d in DIS1.iter() {
+                let (nx, ny) = (x + d[0], y + d[1]);
+                if nx < 0 || nx >= height as i32 || ny < 0 || ny >= width as i32 {
+                    continue;
+                }
+                if let Some(b) = vc.nodes[nx as usize * width + ny as usize] {
+                    if a != b {
+                        vc.add_full(a, b, CellSet::new(), &mut queue);
+                    }
+                }
+            }
+
+            let (line, last) = if player == Player::Red { (x, height) } else { (y, width) };
+            if line == 0 {
+                vc.add_full(a, vc.edge(0), CellSet::new(), &mut queue);
+            }
+            if line == last as i32 - 1 {
+                vc.add_full(a, vc.edge(1), CellSet::new(), &mut queue);
+            }
+        }
+
+        // AND rule: every new full connection is joined with the full connections at both of its ends
+        while let Some((a, b, carrier)) = queue.pop_front() {
+            for (m, o) in [(a, b), (b, a)] {
+                for x in vc.neighbors[m].clone() {
+                    if x == o || vc.carrier_contains(&carrier, x) {
+                        continue;
+                    }
+
+                    for other in vc.full(m, x).to_vec() {
+                        if !other.carrier.is_disjoint(&carrier) || vc.carrier_contains(&other.carrier, o) {
+                            continue;
+                        }
+
+                        let union = carrier | other.carrier;
+                        if vc.is_stone(m) {
+                            vc.add_full(o, x, union, &mut queue);
+                        } else {
+                            let mut union = union;
+                            union.insert(m);
+                            vc.add_semi(o, x, Connection { carrier: union, key: Some(m) }, &mut queue);
+                        }
+                    }
+                }
+            }
+        }
+
+        vc
+    }
+
+    fn find_nodes(&mut self, board: &HexBoard) {
+        let (width, height) = (self.width, self.height);
+        let cell = self.player.to_cell();
+
+        for pos in 0..width * height {
+            if self.nodes[pos].is_some() {
+                continue;
+            }
+
+            let state = board.get_abs(pos);
+            if state == CellState::Empty {
+                self.nodes[pos] = Some(pos);
+            } else if state == cell {
+                let mut stack = vec![pos];
+                self.nodes[pos] = Some(pos);
+                while let Some(p) = stack.pop() {
+                    self.stones.insert(p);
+                    let (x, y) = ((p / width) as i32, (p % width) as i32);
+                    for d in DIS1.iter() {
+                        let (nx, ny) = (x + d[0], y + d[1]);
+                        if nx < 0 || nx >= height as i32 || ny < 0 || ny >= width as i32 {
+                            continue;
+                        }
+                        let next = nx as usize * width + ny as usize;
+                        if self.nodes[next].is_none() && board.get_abs(next) == cell {
+                            self.nodes[next] = Some(pos);
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn is_stone(&self, node: usize) -> bool {
+        node >= self.width * self.height || self.stones.contains(node)
+    }
+
+    fn carrier_contains(&self, carrier: &CellSet, node: usize) -> bool {
+        node < self.width * self.height && carrier.contains(node)
+    }
+
+    fn add_full(&mut self, a: usize, b: usize, carrier: CellSet, queue: &mut VecDeque<(usize, usize, CellSet)>) {
+        let list = self.full.entry(key(a, b)).or_default();
+        if list.iter().any(|x| x.carrier.is_subset(&carrier)) {
+            return;
+        }
+        list.retain(|x| !carrier.is_subset(&x.carrier));
+        if list.len() >= self.config.max_full {
+            return;
+        }
+        list.push(Connection { carrier, key: None });
+
+        if !self.neighbors[a].contains(&b) {
+            self.neighbors[a].push(b);
+            self.neighbors[b].push(a);
+        }
+        if let Some(semi) = self.semi.get_mut(&key(a, b)) {
+            semi.retain(|x| !carrier.is_subset(&x.carrier));
+        }
+        queue.push_back((a, b, carrier));
+    }
+
+    fn add_semi(&mut self, a: usize, b: usize, connection: Connection, queue: &mut VecDeque<(usize, usize, CellSet)>) {
+        let carrier = connection.carrier;
+        if self.full(a, b).iter().any(|x| x.carrier.is_subset(&carrier)) {
+            return;
+        }
+
+        let list = self.semi.entry(key(a, b)).or_default();
+        if list.iter().any(|x| x.carrier.is_subset(&carrier)) {
+            return;
+        }
+        list.retain(|x| !carrier.is_subset(&x.carrier));
+        if list.len() >= self.config.max_semi {
+            return;
+        }
+        list.push(connection);
+
+        // OR rule: greedily add semi connections that shrink the common part of the carriers
+        let mut intersection = carrier;
+        let mut union = carrier;
+        for other in list.iter() {
+            let next = intersection & other.carrier;
+            if next != intersection {
+                intersection = next;
+                union |= other.carrier;
+                if intersection.is_empty() {
+                    break;
+                }
+            }
+        }
+        if intersection.is_empty() {
+            self.add_full(a, b, union, queue);
+        }
+    }
+
+    /// node of edge `index`, 0 is the top edge for red and the left edge for blue
+    pub fn edge(&self, index: usize) -> usize {
+        self.width * self.height + index
+    }
+
+    /// node containing the cell, None for stones of the opponent
+    pub fn node(&self, x: usize, y: usize) -> Option<usize> {
+        self.nodes[x * self.width + y]
+    }
+
+    pub fn full(&self, a: usize, b: usize) -> &[Connection] {
+        self.full.get(&key(a, b)).map(|x| &x[..]).unwrap_or(&[])
+    }
+
+    pub fn semi(&self, a: usize, b: usize) -> &[Connection] {
+        self.semi.get(&key(a, b)).map(|x| &x[..]).unwrap_or(&[])
+    }
+
+    pub fn is_connected(&self, a: usize, b: usize) -> bool {
+        !self.full(a, b).is_empty()
+    }
+
+    /// full connections between the two edges, the player wins whatever the opponent does
+    pub fn edge_full(&self) -> &[Connection] {
+        self.full(self.edge(0), self.edge(1))
+    }
+
+    /// semi connections between the two edges, the player wins by moving at the key
+    pub fn edge_semi(&self) -> &[Connection] {
+        self.semi(self.edge(0), self.edge(1))
+    }
+
+    /// cells where the opponent must move to stop the player from connecting the edges,
+    /// the intersection of the carriers of the edge connections. None when there is no edge connection,
+    /// an empty set when the opponent cannot stop the player
+    pub fn mustplay(&self) -> Option<CellSet> {
+        if !self.edge_full().is_empty() {
+            return Some(CellSet::new());
+        }
+
+        let mut result: Option<CellSet> = None;
+        for x in self.edge_semi().iter() {
+            result = Some(match result {
+                Some(y) => y & x.carrier,
+                None => x.carrier
+            });
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::{CellState, HexBoard};
+    use crate::common::cell_set::CellSet;
+    use crate::common::player::Player;
+    use crate::vc::vc::VcSet;
+
+    #[test]
+    fn test_bridge() {
+        let mut board = HexBoard::new(5);
+        board.set(1, 1, CellState::Red);
+        board.set(2, 2, CellState::Red);
+
+        let vc = VcSet::compute(&board, Player::Red);
+        let (a, b) = (vc.node(1, 1).unwrap(), vc.node(2, 2).unwrap());
+        let bridge: CellSet = [7, 11].into_iter().collect();
+        assert!(vc.full(a, b).iter().any(|x| x.carrier == bridge));
+
+        board.set(1, 2, CellState::Blue);
+        let vc = VcSet::compute(&board, Player::Red);
+        assert!(vc.full(a, b).iter().all(|x| x.carrier.len() > 2));
+        assert!(vc.semi(a, b).iter().any(|x| x.key == Some(11) && x.carrier.len() == 1));
+    }
+
+    #[test]
+    fn test_edge_template() {
+        let mut board = HexBoard::new(5);
+        board.set(1, 1, CellState::Red);
+
+        let vc = VcSet::compute(&board, Player::Red);
+        let a = vc.node(1, 1).unwrap();
+        let template: CellSet = [1, 2].into_iter().collect();
+        assert!(vc.full(a, vc.edge(0)).iter().any(|x| x.carrier == template));
+        assert!(vc.edge_full().is_empty());
+    }
+
+    #[test]
+    fn test_winning() {
+        let mut board = HexBoard::new(3);
+        board.set(1, 1, CellState::Red);
+
+        let vc = VcSet::compute(&board, Player::Red);
+        assert!(vc.edge_full().iter().any(|x| x.carrier.len() == 4));
+        assert_eq!(vc.mustplay(), Some(CellSet::new()));
+
+        let vc = VcSet::compute(&HexBoard::new(3), Player::Red);
+        assert!(vc.edge_semi().iter().any(|x| x.key == Some(4)));
+        assert!(vc.mustplay().unwrap().contains(4));
+
+        let vc = VcSet::compute(&board, Player::Blue);
+        assert!(vc.edge_full().is_empty());
+        assert!(vc.node(1, 1).is_none());
+
+        // blue to move wins on an empty 2x2 board with an obtuse corner
+        let vc = VcSet::compute(&HexBoard::new(2), Player::Blue);
+        assert!(vc.edge_full().is_empty());
+        assert!(vc.edge_semi().iter().any(|x| x.key == Some(1) || x.key == Some(2)));
+    }
+}
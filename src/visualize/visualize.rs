@@ -0,0 +1,160 @@
+use crate::common::board::CellState;
+use crate::common::cell::Cell;
+use crate::common::player::Player;
+use crate::solver::mcts_tree::{Tree, TreeDump};
+
+/// radius of a hexagon of the heatmap in pixels
+const HEX_RADIUS: f64 = 20.0;
+
+fn win_rate(node: &TreeDump) -> f64 {
+    if node.visit == 0 { 0.0 } else { node.win as f64 / node.visit as f64 }
+}
+
+fn player_color(player: Player) -> &'static str {
+    match player {
+        Player::Red => "red",
+        Player::Blue => "blue",
+    }
+}
+
+/// the top `max_depth` levels of the tree in Graphviz DOT.
+/// Every node shows its move, visits, win rate of the player who played it, prior and score,
+/// the border has the color of that player and proven nodes are bold
+pub fn tree_to_dot(tree: &Tree, max_depth: usize) -> String {
+    let dump = tree.dump(max_depth);
+    let mut lines = vec![
+        String::from("digraph tree {"),
+        String::from("    node [shape=box, fontname=\"monospace\"];"),
+        format!("    n0 [label=\"root\\n{} to move\\nvisits {}\"];", player_color(tree.next_player), dump.visit),
+    ];
+
+    let mut count = 1;
+    let mut stack: Vec<(&TreeDump, usize, Player)> = vec![(&dump, 0, tree.next_player)];
+    while let Some((node, id, player)) = stack.pop() {
+        for child in node.children.iter() {
+            let child_id = count;
+            count += 1;
+
+            let mut label = format!(
+                "{}\\nvisits {}\\nwin rate {:.3}\\nprior {:.3}\\nscore {:.3}",
+                child.mov.map(|x| x.to_string()).unwrap_or_default(),
+                child.visit,
+                win_rate(child),
+                child.prior,
+                child.score,
+            );
+            if let Some(winner) = child.proven {
+                label += &format!("\\nproven {}", player_color(winner));
+            }
+            let style = if child.proven.is_some() { ", style=bold" } else { "" };
+            lines.push(format!("    n{} [label=\"{}\", color={}{}];", child_id, label, player_color(player), style));
+            lines.push(format!("    n{} -> n{};", id, child_id));
+
+            stack.push((child, child_id, player.reverse()));
+        }
+    }
+
+    lines.push(String::from("}"));
+    lines.join("\n") + "\n"
+}
+
+/// center of the hexagon of a cell, rows are shifted to the right like the text board
+fn hex_center(cell: Cell) -> (f64, f64) {
+    let dx = HEX_RADIUS * 3f64.sqrt();
+    let x = dx * (cell.y as f64 + cell.x as f64 / 2.0) + dx;
+    let y = HEX_RADIUS * 1.5 * cell.x as f64 + 2.0 * HEX_RADIUS;
+    (x, y)
+}
+
+fn hex_points(cx: f64, cy: f64) -> String {
+    (0..6).map(|i| {
+        let angle = std::f64::consts::PI / 180.0 * (60.0 * i as f64 + 30.0);
+        format!("{:.1},{:.1}", cx + HEX_RADIUS * angle.cos(), cy + HEX_RADIUS * angle.sin())
+    }).collect::<Vec<_>>().join(" ")
+}
+
+/// the root children of the tree on the hex grid as an HTML page with an SVG heatmap.
+/// The stones of the root position are drawn in their color, the moves searched from the root
+/// in orange, stronger for more visits, labeled with the win rate of the player to move
+/// and with all the statistics in the tooltip
+pub fn heatmap_html(tree: &Tree) -> String {
+    let board = &tree.board;
+    let dump = tree.dump(1);
+    let max_visit = dump.children.iter().map(|x| x.visit).max().unwrap_or(0).max(1);
+
+    let (width, _) = hex_center(Cell::new(board.height, board.width));
+    let (_, height) = hex_center(Cell::new(board.height, 0));
+    let mut svg = vec![format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"monospace\" font-size=\"10\" text-anchor=\"middle\">",
+        width, height,
+    )];
+
+    for pos in 0..board.cell_count() {
+        let cell = Cell::from_pos(pos, board.width);
+        let (cx, cy) = hex_center(cell);
+        let child = dump.children.iter().find(|x| x.mov == Some(cell));
+
+        let (fill, opacity) = match (board.get(cell.x, cell.y), child) {
+            (CellState::Red, _) => ("#d03030", 1.0),
+            (CellState::Blue, _) => ("#3050d0", 1.0),
+            (CellState::Empty, Some(child)) => ("#ff8c00", 0.1 + 0.9 * child.visit as f64 / max_visit as f64),
+            (CellState::Empty, None) => ("#ffffff", 1.0),
+        };
+        let title = match child {
+            Some(child) => format!(
+                "{} visits {} win rate {:.3} prior {:.3} score {:.3}",
+                cell, child.visit, win_rate(child), child.prior, child.score,
+            ),
+            None => cell.to_string(),
+        };
+
+        svg.push(format!(
+            "<g><title>{}</title><polygon points=\"{}\" fill=\"{}\" fill-opacity=\"{:.2}\" stroke=\"#808080\"/>",
+            title, hex_points(cx, cy), fill, opacity,
+        ));
+        if let Some(child) = child.filter(|x| x.visit > 0) {
+            svg.push(format!("<text x=\"{:.1}\" y=\"{:.1}\">{:.0}</text>", cx, cy + 3.0, win_rate(child) * 100.0));
+        }
+        svg.push(String::from("</g>"));
+    }
+    svg.push(String::from("</svg>"));
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>hexx search</title></head>\n<body>\n<p>{} to move, {} visits</p>\n{}\n</body>\n</html>\n",
+        player_color(tree.next_player), dump.visit, svg.join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::board::HexBoard;
+    use crate::common::player::Player;
+    use crate::solver::mcts_solver::{MCTSSolver, MCTSSolverConfig};
+    use crate::solver::solver::Solver;
+    use crate::visualize::visualize::{heatmap_html, tree_to_dot};
+
+    #[test]
+    fn test_export() {
+        let config = MCTSSolverConfig {
+            simulation_amount: 2000,
+            ..Default::default()
+        };
+        let mut solver = MCTSSolver::new(config);
+        let mut board = HexBoard::new(4);
+        board.set(1, 2, Player::Red.to_cell());
+        let report = solver.solve(&board, Player::Blue);
+        let tree = solver.tree().unwrap();
+
+        let dot = tree_to_dot(tree, 1);
+        assert!(dot.starts_with("digraph tree {") && dot.ends_with("}\n"));
+        assert_eq!(dot.matches(" -> ").count(), tree.root_node().children().len());
+        assert!(dot.contains(&format!("label=\"{}\\nvisits", report.best_move)));
+        assert!(dot.contains("color=blue"));
+        assert!(tree_to_dot(tree, 2).matches(" -> ").count() > tree.root_node().children().len());
+
+        let html = heatmap_html(tree);
+        assert_eq!(html.matches("<polygon").count(), 16);
+        assert_eq!(html.matches("fill=\"#d03030\"").count(), 1);
+        assert!(html.contains(&format!("<title>{} visits", report.best_move)));
+    }
+}